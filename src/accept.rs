@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MediaRange {
+    /// Media range without its quality, e.g. `text/html`, `text/*` or `text/html;level=1`.
+    pub media_type: String,
+    pub q: f32,
+}
+
+impl MediaRange {
+    /// `*/*` < `type/*` < `type/subtype` < `type/subtype;params`
+    fn specificity(&self) -> u8 {
+        let (range, has_params) = match self.media_type.split_once(';') {
+            Some((range, _)) => (range, true),
+            None => (self.media_type.as_str(), false),
+        };
+
+        match range {
+            "*/*" => 0,
+            r if r.ends_with("/*") => 1,
+            _ if has_params => 3,
+            _ => 2,
+        }
+    }
+
+    /// The `type/subtype` of this range, without its parameters.
+    pub fn essence(&self) -> &str {
+        self.media_type.split(';').next().unwrap_or("")
+    }
+}
+
+fn parse_range(range: &str) -> Option<MediaRange> {
+    let mut params = range.split(';').map(str::trim);
+    let media_type = params.next().filter(|t| t.contains('/'))?;
+
+    let mut q = 1.0;
+    let mut rest = Vec::new();
+    for param in params {
+        match param.split_once('=') {
+            Some((name, value)) if name.trim().eq_ignore_ascii_case("q") => {
+                q = value
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|q| (0.0..=1.0).contains(q))?;
+            }
+            _ => rest.push(param),
+        }
+    }
+
+    let media_type = std::iter::once(media_type)
+        .chain(rest)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    Some(MediaRange { media_type, q })
+}
+
+/// Parses an `Accept` header as defined by RFC 7231, sorted by preference.
+///
+/// Ranges are sorted by quality, then by specificity, then in the order they were sent. Ranges
+/// with an invalid quality are dropped.
+pub fn parse(header: &str) -> Vec<MediaRange> {
+    let mut ranges: Vec<_> = header.split(',').filter_map(parse_range).collect();
+
+    // `sort_by` is stable, so equivalent ranges keep the order they were sent in.
+    ranges.sort_by(|a, b| {
+        b.q.total_cmp(&a.q)
+            .then_with(|| b.specificity().cmp(&a.specificity()))
+    });
+
+    ranges
+}
+
+/// The candidate media type preferred by the client, if it names one of them explicitly.
+///
+/// `ranges` must be sorted by preference, as returned by [`parse`]. Wildcard ranges don't select
+/// any candidate, leaving the choice to the server.
+pub fn preferred<'a>(ranges: &[MediaRange], candidates: &[&'a str]) -> Option<&'a str> {
+    ranges
+        .iter()
+        .filter(|r| r.q > 0.0)
+        .find_map(|r| {
+            candidates
+                .iter()
+                .find(|c| r.essence().eq_ignore_ascii_case(c))
+        })
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn range(media_type: &str, q: f32) -> MediaRange {
+        MediaRange {
+            media_type: media_type.to_string(),
+            q,
+        }
+    }
+
+    #[test]
+    fn test_parse_sorts_by_preference() {
+        let ranges = parse(
+            "text/*;q=0.8, */*;q=0.1, text/html;q=0.8, application/json, \
+             application/xml;q=0.9, text/html;level=1;q=0.8",
+        );
+
+        assert_eq!(
+            ranges,
+            vec![
+                range("application/json", 1.0),
+                range("application/xml", 0.9),
+                range("text/html;level=1", 0.8),
+                range("text/html", 0.8),
+                range("text/*", 0.8),
+                range("*/*", 0.1),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("text/html;q=2")]
+    #[case("text/html;q=abc")]
+    #[case("html")]
+    #[case("")]
+    fn test_parse_drops_invalid_ranges(#[case] header: &str) {
+        assert_eq!(parse(header), vec![]);
+    }
+
+    #[test]
+    fn test_essence() {
+        assert_eq!(range("text/html;level=1", 1.0).essence(), "text/html");
+        assert_eq!(range("text/html", 1.0).essence(), "text/html");
+    }
+
+    #[rstest]
+    #[case("application/xml", Some("application/xml"))]
+    #[case("application/json, application/xml", Some("application/json"))]
+    #[case("application/json;q=0.5, application/xml", Some("application/xml"))]
+    #[case("text/html, */*", None)]
+    #[case("application/xml;q=0", None)]
+    fn test_preferred(#[case] header: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            preferred(&parse(header), &["application/json", "application/xml"]),
+            expected
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Collects echoes into batches, returned as a whole to the request closing them.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Batching {
+    /// How long a batch stays open after its first request.
+    pub window_ms: u64,
+    /// Number of requests closing a batch before its window elapsed.
+    pub max_requests: usize,
+}
+
+#[derive(Debug)]
+struct Batch {
+    opened_at: Instant,
+    echoes: Vec<Value>,
+}
+
+#[derive(Debug)]
+pub struct BatchWindow {
+    window: Duration,
+    max_requests: usize,
+    batch: Mutex<Option<Batch>>,
+}
+
+impl BatchWindow {
+    pub fn new(settings: &Batching) -> Result<Self, String> {
+        if settings.max_requests == 0 {
+            return Err("batching.max_requests must be positive".to_string());
+        }
+
+        Ok(Self {
+            window: Duration::from_millis(settings.window_ms),
+            max_requests: settings.max_requests,
+            batch: Mutex::new(None),
+        })
+    }
+
+    /// Adds the echo to the open batch, opening one if needed. Returns the echoes of the batch if
+    /// the echo closed it, by reaching `max_requests` or by being added after the window elapsed.
+    pub fn add(&self, echo: Value, now: Instant) -> Option<Vec<Value>> {
+        let mut batch = self.batch.lock().unwrap();
+        let open = batch.get_or_insert_with(|| Batch {
+            opened_at: now,
+            echoes: Vec::new(),
+        });
+        open.echoes.push(echo);
+
+        if open.echoes.len() >= self.max_requests
+            || now.duration_since(open.opened_at) >= self.window
+        {
+            batch.take().map(|batch| batch.echoes)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn window(max_requests: usize) -> BatchWindow {
+        BatchWindow::new(&Batching {
+            window_ms: 100,
+            max_requests,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_add_closes_batch_at_max_requests() {
+        let window = window(3);
+        let now = Instant::now();
+
+        assert_eq!(window.add(json!(1), now), None);
+        assert_eq!(window.add(json!(2), now), None);
+        assert_eq!(
+            window.add(json!(3), now),
+            Some(vec![json!(1), json!(2), json!(3)])
+        );
+        assert_eq!(window.add(json!(4), now), None);
+    }
+
+    #[test]
+    fn test_add_closes_batch_after_window() {
+        let window = window(10);
+        let now = Instant::now();
+
+        assert_eq!(window.add(json!(1), now), None);
+        assert_eq!(window.add(json!(2), now + Duration::from_millis(50)), None);
+        assert_eq!(
+            window.add(json!(3), now + Duration::from_millis(100)),
+            Some(vec![json!(1), json!(2), json!(3)])
+        );
+        assert_eq!(window.add(json!(4), now + Duration::from_millis(150)), None);
+    }
+
+    #[test]
+    fn test_new_rejects_empty_batches() {
+        assert!(BatchWindow::new(&Batching {
+            window_ms: 100,
+            max_requests: 0,
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    web, Error, HttpMessage, HttpResponse,
+};
+use std::collections::HashMap;
+
+use crate::AppState;
+
+/// Body size limit applied by actix to the content types without a limit of their own.
+pub const DEFAULT_BODY_LIMIT: usize = 256 * 1024;
+
+/// Request body size limits by content type.
+///
+/// Limits are keyed by media type, e.g. `application/json`, or by a whole type with `type/*`,
+/// e.g. `multipart/*`. An exact match wins over a wildcard one.
+#[derive(Debug)]
+pub struct ContentTypeLimits {
+    limits: HashMap<String, usize>,
+}
+
+impl ContentTypeLimits {
+    pub fn new(limits: &HashMap<String, usize>) -> Self {
+        Self {
+            limits: limits
+                .iter()
+                .map(|(media_type, limit)| (media_type.trim().to_ascii_lowercase(), *limit))
+                .collect(),
+        }
+    }
+
+    /// Limit of the given media type, without its parameters.
+    pub fn limit(&self, media_type: &str) -> usize {
+        let media_type = media_type.to_ascii_lowercase();
+        let wildcard = media_type
+            .split_once('/')
+            .map(|(type_, _)| format!("{}/*", type_));
+
+        self.limits
+            .get(&media_type)
+            .or_else(|| wildcard.and_then(|w| self.limits.get(&w)))
+            .copied()
+            .unwrap_or(DEFAULT_BODY_LIMIT)
+    }
+
+    /// Largest body accepted for any content type, used as the limit when the body is read.
+    pub fn max(&self) -> usize {
+        self.limits
+            .values()
+            .copied()
+            .fold(DEFAULT_BODY_LIMIT, usize::max)
+    }
+}
+
+/// Rejects requests whose `Content-Length` is over the limit of their content type, before their
+/// body is read. Bodies sent without a length are checked once read, by the handler.
+pub async fn limit_body(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let limit = req
+        .app_data::<web::Data<AppState>>()
+        .map(|state| state.content_type_limits.limit(req.content_type()));
+    let length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+
+    if let (Some(limit), Some(length)) = (limit, length) {
+        if length > limit {
+            let resp = HttpResponse::PayloadTooLarge().finish();
+            return Ok(req.into_response(resp).map_into_right_body());
+        }
+    }
+
+    next.call(req).await.map(|res| res.map_into_left_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn limits() -> ContentTypeLimits {
+        ContentTypeLimits::new(&HashMap::from([
+            ("application/json".to_string(), 64 * 1024),
+            ("Multipart/*".to_string(), 10 * 1024 * 1024),
+            ("multipart/mixed".to_string(), 1024),
+        ]))
+    }
+
+    #[rstest]
+    #[case("application/json", 64 * 1024)]
+    #[case("APPLICATION/JSON", 64 * 1024)]
+    #[case("multipart/form-data", 10 * 1024 * 1024)]
+    #[case("multipart/mixed", 1024)]
+    #[case("text/plain", DEFAULT_BODY_LIMIT)]
+    #[case("", DEFAULT_BODY_LIMIT)]
+    fn test_limit(#[case] media_type: &str, #[case] expected: usize) {
+        assert_eq!(limits().limit(media_type), expected);
+    }
+
+    #[test]
+    fn test_max() {
+        assert_eq!(limits().max(), 10 * 1024 * 1024);
+        assert_eq!(
+            ContentTypeLimits::new(&HashMap::new()).max(),
+            DEFAULT_BODY_LIMIT
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Map keeping at most `capacity` entries, evicting the oldest inserted one first. Inserting a key
+/// again replaces its value and makes it the newest entry.
+#[derive(Debug)]
+pub struct BoundedMap<K, V> {
+    capacity: usize,
+    next_seq: u64,
+    entries: HashMap<K, (u64, V)>,
+    /// Keys from oldest to newest, with the sequence number of their insertion. Keys inserted again
+    /// since are left in place, and skipped once they reach the front.
+    order: VecDeque<(u64, K)>,
+}
+
+impl<K: Eq + Hash + Clone, V> BoundedMap<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            next_seq: 0,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.entries.get(key).map(|(_, value)| value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if self.entries.insert(key.clone(), (seq, value)).is_none()
+            && self.entries.len() > self.capacity
+        {
+            self.remove_oldest();
+        }
+        self.order.push_back((seq, key));
+
+        if self.order.len() > 2 * self.capacity {
+            let entries = &self.entries;
+            self.order
+                .retain(|(seq, key)| entries.get(key).is_some_and(|(s, _)| s == seq));
+        }
+    }
+
+    /// Evicts the oldest entries for as long as `evict` holds for them.
+    pub fn evict_oldest_while(&mut self, evict: impl Fn(&V) -> bool) {
+        while let Some(value) = self.oldest() {
+            if !evict(value) {
+                break;
+            }
+            self.remove_oldest();
+        }
+    }
+
+    fn oldest(&mut self) -> Option<&V> {
+        self.skip_replaced();
+        let (_, key) = self.order.front()?;
+
+        self.get(key)
+    }
+
+    fn remove_oldest(&mut self) {
+        self.skip_replaced();
+        if let Some((_, key)) = self.order.pop_front() {
+            self.entries.remove(&key);
+        }
+    }
+
+    /// Drops the keys at the front of `order` that were inserted again since.
+    fn skip_replaced(&mut self) {
+        while let Some((seq, key)) = self.order.front() {
+            if self.entries.get(key).is_some_and(|(s, _)| s == seq) {
+                break;
+            }
+            self.order.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_evicts_oldest_entry() {
+        let mut map = BoundedMap::new(2);
+
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("c", 3);
+
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.get("b"), Some(&2));
+        assert_eq!(map.get("c"), Some(&3));
+    }
+
+    #[test]
+    fn test_insert_again_makes_entry_newest() {
+        let mut map = BoundedMap::new(2);
+
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("a", 3);
+        map.insert("c", 4);
+
+        assert_eq!(map.get("a"), Some(&3));
+        assert_eq!(map.get("b"), None);
+        assert_eq!(map.get("c"), Some(&4));
+    }
+
+    #[test]
+    fn test_insert_keeps_order_bounded() {
+        let mut map = BoundedMap::new(2);
+
+        for i in 0..100 {
+            map.insert("a", i);
+        }
+
+        assert!(map.order.len() <= 4);
+        assert_eq!(map.get("a"), Some(&99));
+    }
+
+    #[test]
+    fn test_insert_without_capacity() {
+        let mut map = BoundedMap::new(0);
+
+        map.insert("a", 1);
+
+        assert_eq!(map.get("a"), None);
+    }
+
+    #[test]
+    fn test_evict_oldest_while() {
+        let mut map = BoundedMap::new(10);
+        for (key, value) in [("a", 1), ("b", 2), ("c", 3), ("d", 1)] {
+            map.insert(key, value);
+        }
+
+        map.evict_oldest_while(|value| *value < 3);
+
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.get("b"), None);
+        assert_eq!(map.get("c"), Some(&3));
+        assert_eq!(map.get("d"), Some(&1));
+    }
+}
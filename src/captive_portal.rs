@@ -0,0 +1,75 @@
+use actix_web::http::header::HeaderMap;
+use serde::Deserialize;
+
+use crate::{cookies, html};
+
+/// Answers the requests without a session cookie with a `511`, as a captive portal does.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CaptivePortal {
+    /// Cookie of an authenticated session.
+    pub session_cookie: String,
+    /// Login page the clients are sent to. Requests to its path are let through.
+    pub login_url: String,
+}
+
+impl CaptivePortal {
+    pub fn requires_login(&self, headers: &HeaderMap, path: &str) -> bool {
+        path != self.login_url
+            && !cookies::parse(headers)
+                .iter()
+                .any(|c| c.name == self.session_cookie && !c.value.is_empty())
+    }
+
+    /// Page linking to the login page, as in the example of RFC 6585.
+    pub fn login_page(&self) -> String {
+        let url = html::escape(&self.login_url);
+
+        format!(
+            "<html><head><title>Network Authentication Required</title>\
+             <meta http-equiv=\"refresh\" content=\"0; url={0}\"></head>\
+             <body><p>You need to <a href=\"{0}\">authenticate with the local network</a> \
+             in order to gain access.</p></body></html>",
+            url
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderValue, COOKIE};
+
+    fn portal() -> CaptivePortal {
+        CaptivePortal {
+            session_cookie: "session".to_string(),
+            login_url: "/login".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_requires_login() {
+        let portal = portal();
+        let mut headers = HeaderMap::new();
+
+        assert!(portal.requires_login(&headers, "/"));
+        assert!(!portal.requires_login(&headers, "/login"));
+
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark; session="));
+        assert!(portal.requires_login(&headers, "/"));
+
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark; session=abc"));
+        assert!(!portal.requires_login(&headers, "/"));
+    }
+
+    #[test]
+    fn test_login_page_escapes_url() {
+        let portal = CaptivePortal {
+            login_url: "https://portal.example/login?a=1&b=2".to_string(),
+            ..portal()
+        };
+
+        assert!(portal
+            .login_page()
+            .contains("<a href=\"https://portal.example/login?a=1&amp;b=2\">"));
+    }
+}
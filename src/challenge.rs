@@ -0,0 +1,56 @@
+use crate::help::SpecialParam;
+
+pub const CHALLENGE_PARAM: &str = "__challenge";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: CHALLENGE_PARAM,
+    value: "Basic|Bearer|Digest",
+    description: "Answers `401` with a `WWW-Authenticate` challenge for the scheme",
+}];
+
+/// Builds the `WWW-Authenticate` challenge for the given scheme, matched case-insensitively.
+///
+/// Digest challenges get a fresh random nonce and opaque value, since there is no actual
+/// authentication to carry over between requests.
+pub fn www_authenticate(scheme: &str, realm: &str) -> Option<String> {
+    match scheme.to_ascii_lowercase().as_str() {
+        "basic" => Some(format!("Basic realm=\"{}\", charset=\"UTF-8\"", realm)),
+        "bearer" => Some(format!("Bearer realm=\"{}\"", realm)),
+        "digest" => Some(format!(
+            "Digest realm=\"{}\", qop=\"auth\", algorithm=SHA-256, nonce=\"{}\", opaque=\"{}\"",
+            realm,
+            hex::encode(rand::random::<[u8; 16]>()),
+            hex::encode(rand::random::<[u8; 16]>()),
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Basic", "Basic realm=\"test\", charset=\"UTF-8\"")]
+    #[case("basic", "Basic realm=\"test\", charset=\"UTF-8\"")]
+    #[case("Bearer", "Bearer realm=\"test\"")]
+    fn test_www_authenticate(#[case] scheme: &str, #[case] expected: &str) {
+        assert_eq!(www_authenticate(scheme, "test"), Some(expected.to_string()));
+    }
+
+    #[test]
+    fn test_www_authenticate_digest() {
+        let challenge = www_authenticate("Digest", "test").unwrap();
+
+        assert!(challenge
+            .starts_with("Digest realm=\"test\", qop=\"auth\", algorithm=SHA-256, nonce=\""));
+        assert!(challenge.contains("opaque=\""));
+        assert_ne!(challenge, www_authenticate("Digest", "test").unwrap());
+    }
+
+    #[test]
+    fn test_www_authenticate_unknown_scheme() {
+        assert_eq!(www_authenticate("Negotiate", "test"), None);
+    }
+}
@@ -0,0 +1,130 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web, Error, HttpResponse,
+};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+use crate::AppState;
+
+/// Limits the number of simultaneous requests per client IP.
+///
+/// actix doesn't expose a hook for when a connection is closed, so the limit applies to the
+/// requests being processed: a slot is taken when a request comes in and released once its
+/// response has been produced.
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    max: usize,
+    active: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+/// Releases the slot taken by a request when dropped.
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    ip: IpAddr,
+    active: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            active: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn acquire(&self, ip: IpAddr) -> Option<ConnectionGuard> {
+        let mut active = self.active.lock().unwrap();
+        let count = active.entry(ip).or_insert(0);
+        if *count >= self.max {
+            return None;
+        }
+        *count += 1;
+
+        Some(ConnectionGuard {
+            ip,
+            active: self.active.clone(),
+        })
+    }
+
+    #[cfg(test)]
+    fn active(&self, ip: IpAddr) -> usize {
+        self.active.lock().unwrap().get(&ip).copied().unwrap_or(0)
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().unwrap();
+        if let Some(count) = active.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                active.remove(&self.ip);
+            }
+        }
+    }
+}
+
+pub async fn limit_connections(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let limiter = req
+        .app_data::<web::Data<AppState>>()
+        .and_then(|state| state.connection_limiter.as_ref());
+    let (limiter, ip) = match (limiter, req.peer_addr()) {
+        (Some(limiter), Some(addr)) => (limiter, addr.ip()),
+        _ => return next.call(req).await.map(|res| res.map_into_left_body()),
+    };
+
+    let _guard = match limiter.acquire(ip) {
+        Some(guard) => guard,
+        None => {
+            let resp = HttpResponse::TooManyRequests().finish();
+            return Ok(req.into_response(resp).map_into_right_body());
+        }
+    };
+
+    next.call(req).await.map(|res| res.map_into_left_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_acquire_enforces_limit_per_ip() {
+        let limiter = ConnectionLimiter::new(2);
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let other_ip = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2));
+
+        let first = limiter.acquire(ip);
+        let second = limiter.acquire(ip);
+
+        assert!(first.is_some());
+        assert!(second.is_some());
+        assert!(limiter.acquire(ip).is_none());
+        assert!(limiter.acquire(other_ip).is_some());
+
+        drop(first);
+
+        assert_eq!(limiter.active(ip), 1);
+        assert!(limiter.acquire(ip).is_some());
+    }
+
+    #[test]
+    fn test_guard_releases_slot() {
+        let limiter = ConnectionLimiter::new(1);
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+
+        let guard = limiter.acquire(ip);
+        assert_eq!(limiter.active(ip), 1);
+
+        drop(guard);
+        assert_eq!(limiter.active(ip), 0);
+    }
+}
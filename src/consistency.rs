@@ -0,0 +1,140 @@
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::bounded::BoundedMap;
+
+#[derive(Debug)]
+struct Write {
+    previous: Option<Value>,
+    value: Value,
+    written_at: Instant,
+}
+
+impl Write {
+    fn visible(&self, delay: Duration, now: Instant) -> Option<&Value> {
+        if now.duration_since(self.written_at) >= delay {
+            Some(&self.value)
+        } else {
+            self.previous.as_ref()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Read {
+    /// The path was never written.
+    Unwritten,
+    /// The path was written, but no value is visible yet.
+    Pending,
+    Visible(Value),
+}
+
+/// Values written by path, only visible to reads once the delay elapsed. Until then, reads see the
+/// value visible before the write.
+///
+/// At most `max_paths` paths are kept, writing to another path drops the oldest write.
+#[derive(Debug)]
+pub struct EventualStore {
+    delay: Duration,
+    writes: Mutex<BoundedMap<String, Write>>,
+}
+
+impl EventualStore {
+    pub fn new(delay_ms: u64, max_paths: usize) -> Self {
+        Self {
+            delay: Duration::from_millis(delay_ms),
+            writes: Mutex::new(BoundedMap::new(max_paths)),
+        }
+    }
+
+    pub fn write(&self, path: &str, value: Value, now: Instant) {
+        let mut writes = self.writes.lock().unwrap();
+        let previous = writes
+            .get(path)
+            .and_then(|write| write.visible(self.delay, now))
+            .cloned();
+
+        writes.insert(
+            path.to_string(),
+            Write {
+                previous,
+                value,
+                written_at: now,
+            },
+        );
+    }
+
+    pub fn read(&self, path: &str, now: Instant) -> Read {
+        let writes = self.writes.lock().unwrap();
+
+        match writes.get(path).map(|write| write.visible(self.delay, now)) {
+            None => Read::Unwritten,
+            Some(None) => Read::Pending,
+            Some(Some(value)) => Read::Visible(value.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_read_returns_previous_value_until_delay_elapsed() {
+        let store = EventualStore::new(100, 10);
+        let now = Instant::now();
+
+        assert_eq!(store.read("/a", now), Read::Unwritten);
+
+        store.write("/a", json!(1), now);
+
+        assert_eq!(store.read("/a", now), Read::Pending);
+        assert_eq!(
+            store.read("/a", now + Duration::from_millis(100)),
+            Read::Visible(json!(1))
+        );
+
+        let now = now + Duration::from_millis(200);
+        store.write("/a", json!(2), now);
+
+        assert_eq!(store.read("/a", now), Read::Visible(json!(1)));
+        assert_eq!(
+            store.read("/a", now + Duration::from_millis(100)),
+            Read::Visible(json!(2))
+        );
+        assert_eq!(store.read("/b", now), Read::Unwritten);
+    }
+
+    #[test]
+    fn test_write_keeps_last_visible_value() {
+        let store = EventualStore::new(100, 10);
+        let now = Instant::now();
+
+        store.write("/a", json!(1), now);
+        store.write("/a", json!(2), now + Duration::from_millis(150));
+        store.write("/a", json!(3), now + Duration::from_millis(200));
+
+        assert_eq!(
+            store.read("/a", now + Duration::from_millis(250)),
+            Read::Visible(json!(1))
+        );
+    }
+
+    #[test]
+    fn test_write_drops_oldest_path_when_full() {
+        let store = EventualStore::new(0, 2);
+        let now = Instant::now();
+
+        store.write("/a", json!(1), now);
+        store.write("/b", json!(2), now + Duration::from_millis(1));
+        store.write("/a", json!(3), now + Duration::from_millis(2));
+        store.write("/c", json!(4), now + Duration::from_millis(3));
+
+        let later = now + Duration::from_millis(10);
+        assert_eq!(store.read("/a", later), Read::Visible(json!(3)));
+        assert_eq!(store.read("/b", later), Read::Unwritten);
+        assert_eq!(store.read("/c", later), Read::Visible(json!(4)));
+    }
+}
@@ -0,0 +1,96 @@
+use actix_web::http::header::{HeaderMap, COOKIE};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+
+/// A cookie as sent by the client.
+///
+/// Browsers only send `name=value` pairs in the `Cookie` header, the attributes (`Path`,
+/// `Expires`, `HttpOnly`, ...) stay on the client side and can't be reflected.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CookieInfo {
+    pub name: String,
+    /// Value with the surrounding quotes removed and percent-encoded characters decoded.
+    pub value: String,
+    /// Value exactly as received.
+    pub raw_value: String,
+    pub quoted: bool,
+}
+
+fn parse_pair(pair: &str) -> Option<CookieInfo> {
+    let (name, raw_value) = pair.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let raw_value = raw_value.trim();
+    let (value, quoted) = match raw_value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+    {
+        Some(v) => (v, true),
+        None => (raw_value, false),
+    };
+
+    Some(CookieInfo {
+        name: name.to_string(),
+        value: percent_decode_str(value).decode_utf8_lossy().to_string(),
+        raw_value: raw_value.to_string(),
+        quoted,
+    })
+}
+
+/// Parses every `Cookie` header, in order. Malformed pairs are skipped.
+pub fn parse(headers: &HeaderMap) -> Vec<CookieInfo> {
+    headers
+        .get_all(COOKIE)
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .filter_map(parse_pair)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+
+    #[test]
+    fn test_parse() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            COOKIE,
+            HeaderValue::from_static("session=\"abc%20def\"; theme=dark;; invalid"),
+        );
+        headers.append(COOKIE, HeaderValue::from_static("empty="));
+
+        assert_eq!(
+            parse(&headers),
+            vec![
+                CookieInfo {
+                    name: "session".to_string(),
+                    value: "abc def".to_string(),
+                    raw_value: "\"abc%20def\"".to_string(),
+                    quoted: true,
+                },
+                CookieInfo {
+                    name: "theme".to_string(),
+                    value: "dark".to_string(),
+                    raw_value: "dark".to_string(),
+                    quoted: false,
+                },
+                CookieInfo {
+                    name: "empty".to_string(),
+                    value: "".to_string(),
+                    raw_value: "".to_string(),
+                    quoted: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_without_cookies() {
+        assert_eq!(parse(&HeaderMap::new()), vec![]);
+    }
+}
@@ -0,0 +1,106 @@
+use actix_web::http::header::HeaderMap;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+pub const DEADLINE_HEADER: &str = "x-deadline";
+
+/// Parses a gRPC `grpc-timeout` value: up to 8 digits followed by a unit (`H`, `M`, `S`, `m`, `u`
+/// or `n`).
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+
+    let (amount, unit) = value.split_at(value.len() - 1);
+    let amount = amount.parse::<u64>().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Parses an absolute `X-Deadline`, either as an RFC 3339 timestamp or as milliseconds since the
+/// Unix epoch, into the time left before it.
+fn parse_deadline(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let deadline = match value.parse::<i64>() {
+        Ok(millis) => DateTime::from_timestamp_millis(millis)?,
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .ok()?
+            .with_timezone(&Utc),
+    };
+
+    Some((deadline - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Time left before the deadline set by the client, if any. When both headers are sent, the
+/// earliest deadline wins.
+pub fn remaining(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let grpc = header(GRPC_TIMEOUT_HEADER).and_then(parse_grpc_timeout);
+    let deadline = header(DEADLINE_HEADER).and_then(|v| parse_deadline(v, now));
+
+    match (grpc, deadline) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1H", Some(Duration::from_secs(3600)))]
+    #[case("2M", Some(Duration::from_secs(120)))]
+    #[case("3S", Some(Duration::from_secs(3)))]
+    #[case("100m", Some(Duration::from_millis(100)))]
+    #[case("5u", Some(Duration::from_micros(5)))]
+    #[case("99999999n", Some(Duration::from_nanos(99999999)))]
+    #[case("100", None)]
+    #[case("m", None)]
+    #[case("100x", None)]
+    #[case("123456789m", None)]
+    fn test_parse_grpc_timeout(#[case] value: &str, #[case] expected: Option<Duration>) {
+        assert_eq!(parse_grpc_timeout(value), expected);
+    }
+
+    #[rstest]
+    #[case("2024-05-01T12:00:01.500Z", Some(Duration::from_millis(1500)))]
+    #[case("2024-05-01T08:00:02-04:00", Some(Duration::from_secs(2)))]
+    #[case("1714564803000", Some(Duration::from_secs(3)))]
+    #[case("2024-05-01T11:00:00Z", Some(Duration::ZERO))]
+    #[case("tomorrow", None)]
+    fn test_parse_deadline(#[case] value: &str, #[case] expected: Option<Duration>) {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(parse_deadline(value, now), expected);
+    }
+
+    #[test]
+    fn test_remaining_uses_earliest_deadline() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(GRPC_TIMEOUT_HEADER),
+            HeaderValue::from_static("2S"),
+        );
+        headers.insert(
+            HeaderName::from_static(DEADLINE_HEADER),
+            HeaderValue::from_static("2024-05-01T12:00:01Z"),
+        );
+
+        assert_eq!(remaining(&headers, now), Some(Duration::from_secs(1)));
+        assert_eq!(remaining(&HeaderMap::new(), now), None);
+    }
+}
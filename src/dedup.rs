@@ -0,0 +1,113 @@
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::bounded::BoundedMap;
+
+/// Answers repeated identical requests with the echo of the first one.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct DedupCache {
+    /// How long an echo is served again to identical requests.
+    pub ttl_ms: u64,
+    /// Maximum number of echoes kept, the oldest one is evicted first.
+    pub max_entries: usize,
+}
+
+pub type ContentHash = [u8; 32];
+
+/// Hash of the method, path and body identifying identical requests.
+pub fn content_hash(method: &str, path: &str, body: &[u8]) -> ContentHash {
+    let mut hasher = Sha256::new();
+    for part in [method.as_bytes(), path.as_bytes(), body] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+
+    hasher.finalize().into()
+}
+
+#[derive(Debug)]
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<BoundedMap<ContentHash, (Instant, Value)>>,
+}
+
+impl ResponseCache {
+    pub fn new(settings: &DedupCache) -> Self {
+        Self {
+            ttl: Duration::from_millis(settings.ttl_ms),
+            entries: Mutex::new(BoundedMap::new(settings.max_entries)),
+        }
+    }
+
+    /// Echo cached for the hash, unless it expired.
+    pub fn get(&self, hash: &ContentHash, now: Instant) -> Option<Value> {
+        let entries = self.entries.lock().unwrap();
+
+        entries
+            .get(hash)
+            .filter(|(cached_at, _)| now.duration_since(*cached_at) < self.ttl)
+            .map(|(_, echo)| echo.clone())
+    }
+
+    pub fn insert(&self, hash: ContentHash, echo: Value, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.evict_oldest_while(|(cached_at, _)| now.duration_since(*cached_at) >= self.ttl);
+        entries.insert(hash, (now, echo));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cache(max_entries: usize) -> ResponseCache {
+        ResponseCache::new(&DedupCache {
+            ttl_ms: 1000,
+            max_entries,
+        })
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = content_hash("POST", "/a", b"body");
+
+        assert_eq!(hash, content_hash("POST", "/a", b"body"));
+        assert_ne!(hash, content_hash("PUT", "/a", b"body"));
+        assert_ne!(hash, content_hash("POST", "/ab", b"ody"));
+    }
+
+    #[test]
+    fn test_get_expires_entries() {
+        let cache = cache(10);
+        let now = Instant::now();
+        let hash = content_hash("GET", "/", b"");
+
+        cache.insert(hash, json!({"id": 1}), now);
+
+        assert_eq!(cache.get(&hash, now), Some(json!({"id": 1})));
+        assert_eq!(cache.get(&hash, now + Duration::from_millis(1000)), None);
+    }
+
+    #[test]
+    fn test_insert_evicts_oldest_entry() {
+        let cache = cache(2);
+        let now = Instant::now();
+        let hashes: Vec<_> = ["/a", "/b", "/c"]
+            .iter()
+            .map(|path| content_hash("GET", path, b""))
+            .collect();
+
+        for (i, hash) in hashes.iter().enumerate() {
+            cache.insert(*hash, json!(i), now + Duration::from_millis(i as u64));
+        }
+
+        let now = now + Duration::from_millis(10);
+        assert_eq!(cache.get(&hashes[0], now), None);
+        assert_eq!(cache.get(&hashes[1], now), Some(json!(1)));
+        assert_eq!(cache.get(&hashes[2], now), Some(json!(2)));
+    }
+}
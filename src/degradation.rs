@@ -0,0 +1,68 @@
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Periodic degradation: in every window of `window_size` requests, the first `healthy_in_window`
+/// ones are answered normally and the others with a `503`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct HealthWindow {
+    pub window_size: u64,
+    pub healthy_in_window: u64,
+}
+
+#[derive(Debug)]
+pub struct DegradationWindow {
+    window_size: u64,
+    healthy_in_window: u64,
+    requests: AtomicU64,
+}
+
+impl DegradationWindow {
+    pub fn new(settings: &HealthWindow) -> Result<Self, String> {
+        if settings.window_size == 0 {
+            return Err("health_window.window_size must be positive".to_string());
+        }
+        if settings.healthy_in_window > settings.window_size {
+            return Err("health_window.healthy_in_window must not exceed window_size".to_string());
+        }
+
+        Ok(Self {
+            window_size: settings.window_size,
+            healthy_in_window: settings.healthy_in_window,
+            requests: AtomicU64::new(0),
+        })
+    }
+
+    /// Counts a request and returns whether it's answered normally.
+    pub fn next_is_healthy(&self) -> bool {
+        let request = self.requests.fetch_add(1, Ordering::Relaxed);
+
+        request % self.window_size < self.healthy_in_window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(window_size: u64, healthy_in_window: u64) -> HealthWindow {
+        HealthWindow {
+            window_size,
+            healthy_in_window,
+        }
+    }
+
+    #[test]
+    fn test_next_is_healthy_follows_the_window() {
+        let window = DegradationWindow::new(&settings(3, 2)).unwrap();
+
+        let healthy: Vec<_> = (0..7).map(|_| window.next_is_healthy()).collect();
+
+        assert_eq!(healthy, vec![true, true, false, true, true, false, true]);
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_settings() {
+        assert!(DegradationWindow::new(&settings(0, 0)).is_err());
+        assert!(DegradationWindow::new(&settings(3, 4)).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use crate::help::SpecialParam;
+
+pub const DELAY_PARAM: &str = "__delay_ms";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: DELAY_PARAM,
+    value: "<milliseconds>",
+    description: "Waits before responding, up to the configured maximum",
+}];
+
+/// Delay requested by `__delay_ms`, capped at `max_ms`. Non-numeric values are ignored.
+pub fn parse(value: &str, max_ms: u64) -> Option<Duration> {
+    value
+        .parse::<u64>()
+        .ok()
+        .map(|ms| Duration::from_millis(ms.min(max_ms)))
+}
+
+/// Delay proportional to the size of the body, at `per_kb_ms` per KiB, capped at `max_ms`.
+pub fn for_body_size(bytes: usize, per_kb_ms: u64, max_ms: u64) -> Duration {
+    let ms = bytes as u128 * per_kb_ms as u128 / 1024;
+
+    Duration::from_millis(ms.min(max_ms as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1500", Some(Duration::from_millis(1500)))]
+    #[case("0", Some(Duration::ZERO))]
+    #[case("60000", Some(Duration::from_millis(30000)))]
+    #[case("-1", None)]
+    #[case("abc", None)]
+    fn test_parse(#[case] value: &str, #[case] expected: Option<Duration>) {
+        assert_eq!(parse(value, 30000), expected);
+    }
+
+    #[rstest]
+    #[case(0, Duration::ZERO)]
+    #[case(512, Duration::from_millis(50))]
+    #[case(10 * 1024, Duration::from_millis(1000))]
+    #[case(1024 * 1024, Duration::from_millis(30000))]
+    fn test_for_body_size(#[case] bytes: usize, #[case] expected: Duration) {
+        assert_eq!(for_body_size(bytes, 100, 30000), expected);
+    }
+}
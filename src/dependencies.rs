@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallMode {
+    /// Dependencies are called one after the other, their delays add up.
+    #[default]
+    Serial,
+    /// Dependencies are called concurrently, the slowest one sets the delay.
+    Parallel,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    pub delay_ms: u64,
+}
+
+/// Downstream dependencies every request is processed across.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct DependencyChain {
+    #[serde(default)]
+    pub mode: CallMode,
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct DependencyCall {
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+impl DependencyChain {
+    pub fn calls(&self) -> Vec<DependencyCall> {
+        self.dependencies
+            .iter()
+            .map(|d| DependencyCall {
+                name: d.name.clone(),
+                duration_ms: d.delay_ms,
+            })
+            .collect()
+    }
+
+    /// Time spent waiting on the dependencies, capped at `max_ms`.
+    pub fn total_delay(&self, max_ms: u64) -> Duration {
+        let delays = self.dependencies.iter().map(|d| d.delay_ms);
+        let total_ms = match self.mode {
+            CallMode::Serial => delays.fold(0, u64::saturating_add),
+            CallMode::Parallel => delays.max().unwrap_or(0),
+        };
+
+        Duration::from_millis(total_ms.min(max_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(CallMode::Serial, 50)]
+    #[case(CallMode::Parallel, 30)]
+    fn test_total_delay(#[case] mode: CallMode, #[case] expected_ms: u64) {
+        let chain = DependencyChain {
+            mode,
+            dependencies: vec![
+                Dependency {
+                    name: "db".to_string(),
+                    delay_ms: 30,
+                },
+                Dependency {
+                    name: "cache".to_string(),
+                    delay_ms: 20,
+                },
+            ],
+        };
+
+        assert_eq!(chain.total_delay(30000), Duration::from_millis(expected_ms));
+    }
+
+    #[rstest]
+    #[case(CallMode::Serial)]
+    #[case(CallMode::Parallel)]
+    fn test_total_delay_is_capped(#[case] mode: CallMode) {
+        let chain = DependencyChain {
+            mode,
+            dependencies: vec![
+                Dependency {
+                    name: "db".to_string(),
+                    delay_ms: u64::MAX,
+                },
+                Dependency {
+                    name: "cache".to_string(),
+                    delay_ms: 20,
+                },
+            ],
+        };
+
+        assert_eq!(chain.total_delay(30000), Duration::from_millis(30000));
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangedField {
+    pub path: String,
+    pub baseline: Value,
+    pub actual: Value,
+}
+
+/// Differences between the baseline of a path and the request body. Fields are identified by
+/// their JSON pointer (RFC 6901), e.g. `/items/0/price`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JsonDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ChangedField>,
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn walk(path: &str, baseline: &Value, actual: &Value, diff: &mut JsonDiff) {
+    match (baseline, actual) {
+        (Value::Object(baseline), Value::Object(actual)) => {
+            for (key, value) in baseline {
+                let path = format!("{}/{}", path, escape(key));
+                match actual.get(key) {
+                    Some(other) => walk(&path, value, other, diff),
+                    None => diff.removed.push(path),
+                }
+            }
+            for key in actual.keys().filter(|k| !baseline.contains_key(*k)) {
+                diff.added.push(format!("{}/{}", path, escape(key)));
+            }
+        }
+        (Value::Array(baseline), Value::Array(actual)) => {
+            for (idx, value) in baseline.iter().enumerate() {
+                let path = format!("{}/{}", path, idx);
+                match actual.get(idx) {
+                    Some(other) => walk(&path, value, other, diff),
+                    None => diff.removed.push(path),
+                }
+            }
+            for idx in baseline.len()..actual.len() {
+                diff.added.push(format!("{}/{}", path, idx));
+            }
+        }
+        _ if baseline != actual => diff.changed.push(ChangedField {
+            path: path.to_string(),
+            baseline: baseline.clone(),
+            actual: actual.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Compares objects and arrays field by field, and any other value as a whole.
+pub fn diff(baseline: &Value, actual: &Value) -> JsonDiff {
+    let mut diff = JsonDiff::default();
+    walk("", baseline, actual, &mut diff);
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff() {
+        let baseline = json!({
+            "id": 1,
+            "name": "widget",
+            "tags": ["a", "b"],
+            "dimensions": {"w": 10, "h": 20},
+            "a/b": true,
+        });
+        let actual = json!({
+            "id": "1",
+            "name": "widget",
+            "tags": ["a"],
+            "dimensions": {"w": 10, "h": 25, "d": 5},
+            "price": 9.99,
+        });
+
+        assert_eq!(
+            diff(&baseline, &actual),
+            JsonDiff {
+                added: vec!["/dimensions/d".to_string(), "/price".to_string()],
+                removed: vec!["/a~1b".to_string(), "/tags/1".to_string()],
+                changed: vec![
+                    ChangedField {
+                        path: "/dimensions/h".to_string(),
+                        baseline: json!(20),
+                        actual: json!(25),
+                    },
+                    ChangedField {
+                        path: "/id".to_string(),
+                        baseline: json!(1),
+                        actual: json!("1"),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_of_equal_values_is_empty() {
+        let value = json!({"id": 1, "tags": ["a"]});
+
+        assert_eq!(diff(&value, &value), JsonDiff::default());
+    }
+}
@@ -0,0 +1,450 @@
+use actix_web::http::header::HeaderMap;
+use base64::{engine::general_purpose::STANDARD as b64engine, Engine as _};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use crate::help::SpecialParam;
+use crate::CatchallResponse;
+
+/// Query parameter selecting an alternative envelope for the echo.
+pub const ECHO_PARAM: &str = "__echo";
+
+pub const ECHO_EVENT: &str = "event";
+pub const CLOUDEVENTS_CONTENT_TYPE: &str = "application/cloudevents+json";
+const CLOUDEVENTS_SPEC_VERSION: &str = "1.0";
+const CLOUDEVENTS_TYPE: &str = "io.catchall.request";
+
+pub const ECHO_NGINX: &str = "nginx";
+/// `$time_local` format of nginx.
+const NGINX_TIME_FORMAT: &str = "%d/%b/%Y:%H:%M:%S %z";
+
+pub const ECHO_AMQP: &str = "amqp";
+const AMQP_APP_ID: &str = "catchall-api";
+const AMQP_MESSAGE_TYPE: &str = "http.request";
+
+pub const ECHO_LOG_EVENT: &str = "logevent";
+/// Source type of the events received by Vector's `http_server` source.
+const LOG_EVENT_SOURCE_TYPE: &str = "http_server";
+
+pub const ECHO_RAW: &str = "1";
+/// Header requesting the raw echo, as `__echo=1` does.
+pub const ECHO_HEADER: &str = "x-catchall-echo";
+
+pub const ECHO_APIGW: &str = "apigw";
+/// Stage of the API Gateway events, there being no deployment behind the catchall.
+const APIGW_STAGE: &str = "$default";
+
+pub const ECHO_DOWNLOAD: &str = "download";
+pub const DOWNLOAD_CONTENT_TYPE: &str = "application/octet-stream";
+pub const DOWNLOAD_CONTENT_DISPOSITION: &str = "attachment; filename=\"request.bin\"";
+
+pub const HELP: &[SpecialParam] = &[
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "event",
+        description: "Wraps the echo in a CloudEvents 1.0 envelope, with the echo as `data`",
+    },
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "logevent",
+        description:
+            "Returns the request as a structured log event, as shipped by Vector or Fluentd",
+    },
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "nginx",
+        description: "Returns the request as an nginx `combined` access log line",
+    },
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "amqp",
+        description:
+            "Returns the request as an AMQP 0-9-1 message: its basic properties and its base64 body",
+    },
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "1",
+        description: "Returns the raw request body with its content type, like an echo server",
+    },
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "apigw",
+        description: "Returns the request as an AWS API Gateway proxy integration event",
+    },
+    SpecialParam {
+        name: ECHO_PARAM,
+        value: "download",
+        description: "Returns the raw request body as an attachment, for browsers to save it",
+    },
+];
+
+/// Whether the raw request body is echoed back, with `__echo=1` or `X-Catchall-Echo: true`.
+pub fn raw_echo_requested(echo: Option<&str>, headers: &HeaderMap) -> bool {
+    echo == Some(ECHO_RAW)
+        || headers
+            .get(ECHO_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+/// Wraps the echo in a CloudEvents 1.0 envelope, in structured JSON mode.
+///
+/// The request ID is used as the event ID, and the URL the request was sent to as its source.
+pub fn cloud_event(resp: &CatchallResponse, time: DateTime<Utc>) -> Value {
+    json!({
+        "specversion": CLOUDEVENTS_SPEC_VERSION,
+        "type": CLOUDEVENTS_TYPE,
+        "source": format!(
+            "{}://{}:{}{}",
+            resp.url.scheme, resp.url.hostname, resp.url.port, resp.path
+        ),
+        "id": resp.request_id,
+        "time": time.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "datacontenttype": "application/json",
+        "data": resp,
+    })
+}
+
+/// Escapes a variable as nginx does in its access logs.
+fn nginx_escape(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            c if c == '"' || c == '\\' || c.is_ascii_control() => format!("\\x{:02X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// User of a `Basic` authorization, as in `$remote_user`.
+fn basic_auth_user(resp: &CatchallResponse) -> Option<String> {
+    let (scheme, credentials) = resp.header("authorization")?.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let credentials = String::from_utf8(b64engine.decode(credentials.trim()).ok()?).ok()?;
+
+    credentials
+        .split_once(':')
+        .map(|(user, _)| user.to_string())
+}
+
+/// Formats the request as a line of the nginx `combined` access log format:
+///
+/// `$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer"
+/// "$http_user_agent"`
+///
+/// The line being the response body, `$body_bytes_sent` is the size of the request body instead.
+pub fn nginx_access_log(
+    resp: &CatchallResponse,
+    request_line: &str,
+    status: u16,
+    body_bytes: usize,
+    time: DateTime<Utc>,
+) -> String {
+    let variable = |value: Option<&str>| match value {
+        Some(value) if !value.is_empty() => nginx_escape(value),
+        _ => "-".to_string(),
+    };
+
+    format!(
+        "{} - {} [{}] \"{}\" {} {} \"{}\" \"{}\"",
+        variable(resp.client.remote_ip.as_deref()),
+        variable(basic_auth_user(resp).as_deref()),
+        time.format(NGINX_TIME_FORMAT),
+        nginx_escape(request_line),
+        status,
+        body_bytes,
+        variable(resp.header("referer")),
+        variable(resp.header("user-agent")),
+    )
+}
+
+/// Maps the request onto an AMQP 0-9-1 message: its basic properties and its body.
+///
+/// The request headers become the message headers, with repeated ones joined by commas, along with
+/// the method and path of the request.
+/// The body is kept base64-encoded, as in `body.raw`.
+pub fn amqp_message(resp: &CatchallResponse, time: DateTime<Utc>) -> Value {
+    let mut headers: Map<String, Value> = resp
+        .headers
+        .iter()
+        .map(|(name, values)| (name.clone(), json!(values.join(", "))))
+        .collect();
+    headers.insert("x-http-method".to_string(), json!(resp.method));
+    headers.insert("x-http-path".to_string(), json!(resp.path));
+
+    json!({
+        "properties": {
+            "content_type": resp.header("content-type"),
+            "content_encoding": resp.header("content-encoding"),
+            "headers": headers,
+            "message_id": resp.request_id,
+            "timestamp": time.timestamp(),
+            "type": AMQP_MESSAGE_TYPE,
+            "app_id": AMQP_APP_ID,
+        },
+        "body": resp.body.raw,
+        "body_encoding": resp.body.raw_encoding.as_deref().unwrap_or("base64"),
+    })
+}
+
+/// Formats the request as the event an AWS API Gateway proxy integration (REST API, payload
+/// format 1.0) passes to a Lambda function.
+///
+/// As in API Gateway, `headers` and `queryStringParameters` hold the last value of repeated ones,
+/// and the body is kept as is if it's UTF-8 and base64-encoded otherwise.
+pub fn api_gateway_event(resp: &CatchallResponse, body: &[u8], time: DateTime<Utc>) -> Value {
+    let last_values = |values: &HashMap<String, Vec<String>>| -> Option<Map<String, Value>> {
+        (!values.is_empty()).then(|| {
+            values
+                .iter()
+                .filter_map(|(name, values)| Some((name.clone(), json!(values.last()?))))
+                .collect()
+        })
+    };
+    let multi_values = |values: &HashMap<String, Vec<String>>| -> Option<Map<String, Value>> {
+        (!values.is_empty()).then(|| {
+            values
+                .iter()
+                .map(|(name, values)| (name.clone(), json!(values)))
+                .collect()
+        })
+    };
+    let (body, is_base64_encoded) = match std::str::from_utf8(body) {
+        _ if body.is_empty() => (None, false),
+        Ok(text) => (Some(text.to_string()), false),
+        Err(_) => (Some(b64engine.encode(body)), true),
+    };
+
+    json!({
+        "resource": "/{proxy+}",
+        "path": resp.path,
+        "httpMethod": resp.method,
+        "headers": last_values(&resp.headers),
+        "multiValueHeaders": multi_values(&resp.headers),
+        "queryStringParameters": last_values(&resp.query_params),
+        "multiValueQueryStringParameters": multi_values(&resp.query_params),
+        "pathParameters": {"proxy": resp.path.trim_start_matches('/')},
+        "stageVariables": null,
+        "requestContext": {
+            "requestId": resp.request_id,
+            "resourcePath": "/{proxy+}",
+            "httpMethod": resp.method,
+            "path": resp.path,
+            "protocol": resp.http_version,
+            "stage": APIGW_STAGE,
+            "domainName": resp.url.hostname,
+            "requestTimeEpoch": time.timestamp_millis(),
+            "identity": {
+                "sourceIp": resp.client.remote_ip,
+                "userAgent": resp.header("user-agent"),
+            },
+        },
+        "body": body,
+        "isBase64Encoded": is_base64_encoded,
+    })
+}
+
+/// Formats the request as a structured log event, as shipped by Vector or Fluentd, with the echo
+/// nested under `request`.
+pub fn log_event(resp: &CatchallResponse, time: DateTime<Utc>) -> Value {
+    json!({
+        "@timestamp": time.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "message": format!("{} {}", resp.method, resp.path),
+        "host": resp.url.hostname,
+        "source_type": LOG_EVENT_SOURCE_TYPE,
+        "request": resp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, ClientInfo, UrlInfo};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cloud_event() {
+        let resp = CatchallResponse {
+            request_id: "abc".to_string(),
+            method: "POST".to_string(),
+            path: "/orders".to_string(),
+            url: UrlInfo {
+                scheme: "http".to_string(),
+                hostname: "localhost".to_string(),
+                port: 8080,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let event = cloud_event(&resp, time);
+
+        assert_eq!(event["specversion"], "1.0");
+        assert_eq!(event["type"], "io.catchall.request");
+        assert_eq!(event["source"], "http://localhost:8080/orders");
+        assert_eq!(event["id"], "abc");
+        assert_eq!(event["time"], "2024-05-01T12:00:00.000Z");
+        assert_eq!(event["data"], serde_json::to_value(&resp).unwrap());
+    }
+
+    #[test]
+    fn test_log_event() {
+        let resp = CatchallResponse {
+            method: "GET".to_string(),
+            path: "/orders".to_string(),
+            url: UrlInfo {
+                hostname: "localhost".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let event = log_event(&resp, time);
+
+        assert_eq!(event["@timestamp"], "2024-05-01T12:00:00.000Z");
+        assert_eq!(event["message"], "GET /orders");
+        assert_eq!(event["host"], "localhost");
+        assert_eq!(event["source_type"], "http_server");
+        assert_eq!(event["request"], serde_json::to_value(&resp).unwrap());
+    }
+
+    #[test]
+    fn test_nginx_access_log() {
+        let resp = CatchallResponse {
+            client: ClientInfo {
+                remote_ip: Some("192.168.0.1".to_string()),
+                ..Default::default()
+            },
+            headers: HashMap::from([
+                (
+                    "authorization".to_string(),
+                    vec![format!("Basic {}", b64engine.encode("alice:secret"))],
+                ),
+                (
+                    "user-agent".to_string(),
+                    vec!["curl/8.0 \"test\"".to_string()],
+                ),
+            ]),
+            ..Default::default()
+        };
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            nginx_access_log(&resp, "GET /orders?page=2 HTTP/1.1", 200, 0, time),
+            "192.168.0.1 - alice [01/May/2024:12:00:00 +0000] \"GET /orders?page=2 HTTP/1.1\" \
+             200 0 \"-\" \"curl/8.0 \\x22test\\x22\""
+        );
+    }
+
+    #[test]
+    fn test_amqp_message() {
+        let resp = CatchallResponse {
+            request_id: "abc".to_string(),
+            method: "POST".to_string(),
+            path: "/orders".to_string(),
+            headers: HashMap::from([(
+                "content-type".to_string(),
+                vec!["application/json".to_string()],
+            )]),
+            body: Body {
+                raw: "e30=".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            amqp_message(&resp, time),
+            json!({
+                "properties": {
+                    "content_type": "application/json",
+                    "content_encoding": null,
+                    "headers": {
+                        "content-type": "application/json",
+                        "x-http-method": "POST",
+                        "x-http-path": "/orders",
+                    },
+                    "message_id": "abc",
+                    "timestamp": 1714564800,
+                    "type": "http.request",
+                    "app_id": "catchall-api",
+                },
+                "body": "e30=",
+                "body_encoding": "base64",
+            })
+        );
+    }
+
+    #[test]
+    fn test_api_gateway_event() {
+        let resp = CatchallResponse {
+            request_id: "abc".to_string(),
+            method: "POST".to_string(),
+            http_version: "HTTP/1.1".to_string(),
+            path: "/orders/42".to_string(),
+            headers: HashMap::from([(
+                "accept".to_string(),
+                vec!["text/plain".to_string(), "application/json".to_string()],
+            )]),
+            query_params: HashMap::from([("tag".to_string(), vec!["a".to_string()])]),
+            ..Default::default()
+        };
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let event = api_gateway_event(&resp, br#"{"id": 42}"#, time);
+
+        assert_eq!(event["httpMethod"], "POST");
+        assert_eq!(event["path"], "/orders/42");
+        assert_eq!(event["headers"], json!({"accept": "application/json"}));
+        assert_eq!(
+            event["multiValueHeaders"],
+            json!({"accept": ["text/plain", "application/json"]})
+        );
+        assert_eq!(event["queryStringParameters"], json!({"tag": "a"}));
+        assert_eq!(event["pathParameters"], json!({"proxy": "orders/42"}));
+        assert_eq!(event["requestContext"]["requestId"], "abc");
+        assert_eq!(
+            event["requestContext"]["requestTimeEpoch"],
+            1714564800000i64
+        );
+        assert_eq!(event["body"], r#"{"id": 42}"#);
+        assert_eq!(event["isBase64Encoded"], false);
+    }
+
+    #[test]
+    fn test_api_gateway_event_body_encoding() {
+        let resp = CatchallResponse::default();
+        let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let event = api_gateway_event(&resp, &[0xff, 0x00], time);
+        assert_eq!(event["body"], "/wA=");
+        assert_eq!(event["isBase64Encoded"], true);
+
+        let event = api_gateway_event(&resp, b"", time);
+        assert_eq!(event["body"], Value::Null);
+        assert_eq!(event["headers"], Value::Null);
+        assert_eq!(event["queryStringParameters"], Value::Null);
+        assert_eq!(event["isBase64Encoded"], false);
+    }
+
+    #[test]
+    fn test_raw_echo_requested() {
+        let mut headers = HeaderMap::new();
+
+        assert!(raw_echo_requested(Some("1"), &headers));
+        assert!(!raw_echo_requested(Some("event"), &headers));
+        assert!(!raw_echo_requested(None, &headers));
+
+        headers.insert(ECHO_HEADER.parse().unwrap(), "True".parse().unwrap());
+        assert!(raw_echo_requested(None, &headers));
+
+        headers.insert(ECHO_HEADER.parse().unwrap(), "false".parse().unwrap());
+        assert!(!raw_echo_requested(None, &headers));
+    }
+}
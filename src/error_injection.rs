@@ -0,0 +1,96 @@
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use std::sync::Mutex;
+
+use crate::path_value::PathValue;
+
+/// Randomly fails requests, with a rate that can be set per path prefix.
+#[derive(Debug)]
+pub struct ErrorInjector {
+    /// Path prefixes and their error rate, longest prefix first.
+    path_rates: Vec<(String, f64)>,
+    default_rate: f64,
+    rng: Mutex<StdRng>,
+}
+
+impl ErrorInjector {
+    /// Prefixes can end with a `*` wildcard, e.g. `/flaky/*`, which is equivalent to `/flaky/`.
+    pub fn new(path_rates: &[PathValue<f64>], default_rate: f64, seed: Option<u64>) -> Self {
+        let mut path_rates: Vec<_> = path_rates
+            .iter()
+            .map(|rate| (rate.path.trim_end_matches('*').to_string(), rate.value))
+            .collect();
+        path_rates.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => rand::make_rng(),
+        };
+
+        Self {
+            path_rates,
+            default_rate,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Error rate of the longest matching prefix, or the default rate if none match.
+    pub fn rate(&self, path: &str) -> f64 {
+        self.path_rates
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map_or(self.default_rate, |(_, rate)| *rate)
+    }
+
+    pub fn should_fail(&self, path: &str) -> bool {
+        let rate = self.rate(path).clamp(0.0, 1.0);
+
+        rate > 0.0 && self.rng.lock().unwrap().random_bool(rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn injector(default_rate: f64) -> ErrorInjector {
+        let path_rates = vec![
+            PathValue::new("/api/*", 0.1),
+            PathValue::new("/api/flaky/*", 1.0),
+            PathValue::new("/api/stable", 0.0),
+        ];
+
+        ErrorInjector::new(&path_rates, default_rate, Some(42))
+    }
+
+    #[rstest]
+    #[case("/api/flaky/orders", 1.0)]
+    #[case("/api/stable/orders", 0.0)]
+    #[case("/api/other", 0.1)]
+    #[case("/other", 0.25)]
+    fn test_rate_uses_longest_prefix(#[case] path: &str, #[case] expected: f64) {
+        assert_eq!(injector(0.25).rate(path), expected);
+    }
+
+    #[test]
+    fn test_should_fail_follows_rate() {
+        let injector = injector(0.5);
+
+        assert!((0..100).all(|_| injector.should_fail("/api/flaky/orders")));
+        assert!((0..100).all(|_| !injector.should_fail("/api/stable")));
+
+        let failures = (0..1000).filter(|_| injector.should_fail("/other")).count();
+        assert!((400..600).contains(&failures));
+    }
+
+    #[test]
+    fn test_should_fail_is_reproducible_with_a_seed() {
+        let a = injector(0.5);
+        let b = injector(0.5);
+
+        let a: Vec<_> = (0..100).map(|_| a.should_fail("/other")).collect();
+        let b: Vec<_> = (0..100).map(|_| b.should_fail("/other")).collect();
+
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,81 @@
+use serde_json::Value;
+
+use crate::help::SpecialParam;
+
+pub const FIELDS_PARAM: &str = "__fields";
+pub const EXCLUDE_PARAM: &str = "__exclude";
+
+pub const HELP: &[SpecialParam] = &[
+    SpecialParam {
+        name: FIELDS_PARAM,
+        value: "<field>,<field>,...",
+        description: "Returns only the listed top-level fields of the echo",
+    },
+    SpecialParam {
+        name: EXCLUDE_PARAM,
+        value: "<field>,<field>,...",
+        description: "Drops the listed top-level fields from the echo",
+    },
+];
+
+/// Top-level fields of the echo kept with `__fields` and dropped with `__exclude`, as
+/// comma-separated lists.
+#[derive(Debug, Default)]
+pub struct FieldFilter<'a> {
+    include: Option<&'a str>,
+    exclude: Option<&'a str>,
+}
+
+fn names(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+impl<'a> FieldFilter<'a> {
+    pub fn new(include: Option<&'a str>, exclude: Option<&'a str>) -> Self {
+        Self { include, exclude }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    pub fn apply(&self, value: Value) -> Value {
+        let Value::Object(mut fields) = value else {
+            return value;
+        };
+
+        if let Some(include) = self.include {
+            let include: Vec<_> = names(include).collect();
+            fields.retain(|name, _| include.contains(&name.as_str()));
+        }
+        for name in self.exclude.into_iter().flat_map(names) {
+            fields.remove(name);
+        }
+
+        Value::Object(fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(None, None, json!({"method": "GET", "path": "/", "body": {"raw": ""}}))]
+    #[case(Some("method, path"), None, json!({"method": "GET", "path": "/"}))]
+    #[case(None, Some("body,missing"), json!({"method": "GET", "path": "/"}))]
+    #[case(Some("method,path"), Some("path"), json!({"method": "GET"}))]
+    fn test_apply(
+        #[case] include: Option<&str>,
+        #[case] exclude: Option<&str>,
+        #[case] expected: Value,
+    ) {
+        let echo = json!({"method": "GET", "path": "/", "body": {"raw": ""}});
+
+        assert_eq!(FieldFilter::new(include, exclude).apply(echo), expected);
+    }
+}
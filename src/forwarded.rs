@@ -0,0 +1,84 @@
+use actix_web::http::header::{HeaderMap, HeaderName, FORWARDED};
+
+pub const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+pub const X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+
+/// Value of a parameter of the first `Forwarded` element, e.g. `proto` (RFC 7239).
+fn forwarded_param(headers: &HeaderMap, param: &str) -> Option<String> {
+    let header = headers.get(FORWARDED)?.to_str().ok()?;
+    let element = header.split(',').next()?;
+
+    element.split(';').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case(param)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Value set by the closest proxy in an `X-Forwarded-*` header.
+fn x_forwarded(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    let header = headers.get(name)?.to_str().ok()?;
+
+    Some(header.split(',').next()?.trim().to_string())
+}
+
+/// Scheme the client used to reach the proxy, as actix picks it: from `Forwarded`, then from
+/// `X-Forwarded-Proto`.
+pub fn proto(headers: &HeaderMap) -> Option<String> {
+    forwarded_param(headers, "proto").or_else(|| x_forwarded(headers, &X_FORWARDED_PROTO))
+}
+
+/// Host the client used to reach the proxy, as actix picks it: from `Forwarded`, then from
+/// `X-Forwarded-Host`.
+pub fn host(headers: &HeaderMap) -> Option<String> {
+    forwarded_param(headers, "host").or_else(|| x_forwarded(headers, &X_FORWARDED_HOST))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+
+    fn headers(pairs: &[(HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name.clone(), HeaderValue::from_static(value));
+        }
+
+        headers
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let headers = headers(&[
+            (
+                FORWARDED,
+                "for=192.0.2.60;Proto=https;host=\"api.example:8443\", proto=http",
+            ),
+            (X_FORWARDED_PROTO, "http"),
+        ]);
+
+        assert_eq!(proto(&headers), Some("https".to_string()));
+        assert_eq!(host(&headers), Some("api.example:8443".to_string()));
+    }
+
+    #[test]
+    fn test_x_forwarded_headers() {
+        let headers = headers(&[
+            (X_FORWARDED_PROTO, "https, http"),
+            (X_FORWARDED_HOST, "api.example"),
+        ]);
+
+        assert_eq!(proto(&headers), Some("https".to_string()));
+        assert_eq!(host(&headers), Some("api.example".to_string()));
+    }
+
+    #[test]
+    fn test_not_forwarded() {
+        let headers = headers(&[(FORWARDED, "for=192.0.2.60")]);
+
+        assert_eq!(proto(&headers), None);
+        assert_eq!(host(&headers), None);
+    }
+}
@@ -0,0 +1,157 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web, Error,
+};
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use serde::Deserialize;
+use std::sync::Mutex;
+
+use crate::AppState;
+
+/// Prefix of every injected header, so they can't replace the ones set by the server.
+const HEADER_PREFIX: &str = "x-fuzz-";
+const MAX_NAME_LEN: usize = 16;
+const MAX_VALUE_LEN: usize = 64;
+
+/// Characters allowed in a header name (RFC 9110 `tchar`), which are also valid in a value.
+const TOKEN_SPECIALS: &str = "!#$%&'*+-.^_`|~";
+
+fn default_charset() -> String {
+    ('a'..='z').chain('A'..='Z').chain('0'..='9').collect()
+}
+
+/// Random headers injected in every response.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct FuzzHeaders {
+    /// Number of headers added to each response.
+    pub count: usize,
+    /// Seed of the generator, so runs can be replayed. Random when unset.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Characters the header names and values are made of.
+    #[serde(default = "default_charset")]
+    pub charset: String,
+}
+
+/// Generates the fuzzed headers from a single generator shared by every request, so a given seed
+/// always yields the same sequence of headers.
+#[derive(Debug)]
+pub struct HeaderFuzzer {
+    count: usize,
+    charset: Vec<char>,
+    rng: Mutex<StdRng>,
+}
+
+impl HeaderFuzzer {
+    pub fn new(settings: &FuzzHeaders) -> Result<Self, String> {
+        let charset: Vec<char> = settings.charset.chars().collect();
+        if charset.is_empty() {
+            return Err("fuzz_headers.charset must not be empty".to_string());
+        }
+        if let Some(c) = charset
+            .iter()
+            .find(|c| !c.is_ascii_alphanumeric() && !TOKEN_SPECIALS.contains(**c))
+        {
+            return Err(format!(
+                "fuzz_headers.charset contains '{}', which isn't allowed in a header name",
+                c
+            ));
+        }
+
+        let rng = match settings.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => rand::make_rng(),
+        };
+
+        Ok(Self {
+            count: settings.count,
+            charset,
+            rng: Mutex::new(rng),
+        })
+    }
+
+    pub fn generate(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut rng = self.rng.lock().unwrap();
+        let mut random_string = |max_len: usize| -> String {
+            let len = rng.random_range(1..=max_len);
+            (0..len)
+                .map(|_| self.charset[rng.random_range(0..self.charset.len())])
+                .collect()
+        };
+
+        (0..self.count)
+            .map(|_| {
+                let name = format!("{}{}", HEADER_PREFIX, random_string(MAX_NAME_LEN));
+                let value = random_string(MAX_VALUE_LEN);
+                (
+                    HeaderName::try_from(name).expect("charset is validated"),
+                    HeaderValue::try_from(value).expect("charset is validated"),
+                )
+            })
+            .collect()
+    }
+}
+
+pub async fn inject_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+
+    let mut res = next.call(req).await?;
+
+    if let Some(fuzzer) = state.as_ref().and_then(|s| s.header_fuzzer.as_ref()) {
+        let headers = res.headers_mut();
+        for (name, value) in fuzzer.generate() {
+            headers.append(name, value);
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(seed: Option<u64>, charset: &str) -> FuzzHeaders {
+        FuzzHeaders {
+            count: 5,
+            seed,
+            charset: charset.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_generate_is_reproducible_with_a_seed() {
+        let a = HeaderFuzzer::new(&settings(Some(42), &default_charset())).unwrap();
+        let b = HeaderFuzzer::new(&settings(Some(42), &default_charset())).unwrap();
+
+        let headers = a.generate();
+
+        assert_eq!(headers.len(), 5);
+        assert_eq!(headers, b.generate());
+        assert_ne!(headers, a.generate());
+    }
+
+    #[test]
+    fn test_generate_uses_charset() {
+        let fuzzer = HeaderFuzzer::new(&settings(None, "ab|")).unwrap();
+
+        for (name, value) in fuzzer.generate() {
+            let name = name.as_str().strip_prefix(HEADER_PREFIX).unwrap();
+            assert!(name.chars().all(|c| "ab|".contains(c)));
+            assert!(value.as_bytes().iter().all(|c| b"ab|".contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_charset() {
+        assert!(HeaderFuzzer::new(&settings(None, "")).is_err());
+        assert!(HeaderFuzzer::new(&settings(None, "ab:")).is_err());
+        assert!(HeaderFuzzer::new(&settings(None, "a b")).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use maxminddb::{geoip2, MaxMindDbError, Reader};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::net::IpAddr;
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeoInfo {
+    pub country: Option<String>,
+    pub city: Option<String>,
+    pub asn: Option<u32>,
+}
+
+pub trait GeoLookup: Debug + Send + Sync {
+    fn lookup(&self, ip: IpAddr) -> Option<GeoInfo>;
+}
+
+/// Looks up addresses in MaxMind GeoIP2/GeoLite2 databases.
+///
+/// Country and city come from a City database while the ASN comes from an optional ASN database,
+/// since MaxMind ships them separately.
+pub struct MaxMindLookup {
+    city: Reader<Vec<u8>>,
+    asn: Option<Reader<Vec<u8>>>,
+}
+
+impl Debug for MaxMindLookup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaxMindLookup")
+            .field("city", &self.city.metadata().database_type)
+            .field(
+                "asn",
+                &self.asn.as_ref().map(|r| &r.metadata().database_type),
+            )
+            .finish()
+    }
+}
+
+impl MaxMindLookup {
+    pub fn open(city_path: &str, asn_path: Option<&str>) -> Result<Self, MaxMindDbError> {
+        let city = Reader::open_readfile(city_path)?;
+        let asn = asn_path.map(Reader::open_readfile).transpose()?;
+
+        Ok(Self { city, asn })
+    }
+}
+
+impl GeoLookup for MaxMindLookup {
+    fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+        let city = self
+            .city
+            .lookup(ip)
+            .ok()
+            .and_then(|r| r.decode::<geoip2::City>().ok().flatten());
+        let asn = self.asn.as_ref().and_then(|reader| {
+            reader
+                .lookup(ip)
+                .ok()
+                .and_then(|r| r.decode::<geoip2::Asn>().ok().flatten())
+                .and_then(|a| a.autonomous_system_number)
+        });
+
+        if city.is_none() && asn.is_none() {
+            return None;
+        }
+
+        Some(GeoInfo {
+            country: city
+                .as_ref()
+                .and_then(|c| c.country.iso_code)
+                .map(|c| c.to_string()),
+            city: city
+                .as_ref()
+                .and_then(|c| c.city.names.english)
+                .map(|c| c.to_string()),
+            asn,
+        })
+    }
+}
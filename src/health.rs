@@ -0,0 +1,14 @@
+use actix_web::HttpResponse;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub status: String,
+}
+
+/// Answers health checks, without echoing or logging them.
+pub async fn health() -> HttpResponse {
+    HttpResponse::Ok().json(HealthStatus {
+        status: "ok".to_string(),
+    })
+}
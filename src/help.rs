@@ -0,0 +1,61 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::{
+    challenge, delay, envelope, fields, infinite, latency, malformed, pagination, redirect_loop,
+    slow_read, split, stable, status, trailer,
+};
+
+pub const HELP_PATH: &str = "/__help";
+
+/// A special query parameter, as listed by `/__help`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SpecialParam {
+    pub name: &'static str,
+    /// Format of the value, e.g. `<bytes per second>` or `event|amqp`.
+    pub value: &'static str,
+    pub description: &'static str,
+}
+
+/// Special query parameters of every feature, each of them declaring its own in a `HELP` constant.
+const REGISTRY: &[&[SpecialParam]] = &[
+    pagination::HELP,
+    split::HELP,
+    latency::HELP,
+    challenge::HELP,
+    malformed::HELP,
+    redirect_loop::HELP,
+    envelope::HELP,
+    trailer::HELP,
+    slow_read::HELP,
+    infinite::HELP,
+    status::HELP,
+    delay::HELP,
+    stable::HELP,
+    fields::HELP,
+];
+
+pub fn catalog() -> Vec<&'static SpecialParam> {
+    REGISTRY.iter().flat_map(|params| params.iter()).collect()
+}
+
+pub async fn help() -> HttpResponse {
+    HttpResponse::Ok().json(catalog())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalog_params_are_documented() {
+        let catalog = catalog();
+
+        assert!(catalog.iter().all(|p| p.name.starts_with("__")));
+        assert!(catalog.iter().all(|p| !p.description.is_empty()));
+
+        let entries: HashSet<_> = catalog.iter().map(|p| (p.name, p.value)).collect();
+        assert_eq!(entries.len(), catalog.len());
+    }
+}
@@ -0,0 +1,142 @@
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::AppState;
+
+pub const HISTORY_PATH: &str = "/__history";
+pub const REQUESTS_PATH: &str = "/__requests";
+
+const DEFAULT_PER_PAGE: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+/// A page of the recorded echoes, newest first.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+    pub total_pages: usize,
+    pub requests: Vec<Value>,
+}
+
+/// The echoes of the last requests handled by the catchall.
+#[derive(Debug)]
+pub struct RequestHistory {
+    capacity: usize,
+    echoes: Mutex<VecDeque<Value>>,
+}
+
+impl RequestHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            echoes: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Records an echo, evicting the oldest one once the history is full.
+    pub fn record(&self, echo: Value) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut echoes = self.echoes.lock().unwrap();
+        if echoes.len() == self.capacity {
+            echoes.pop_back();
+        }
+        echoes.push_front(echo);
+    }
+
+    /// Recorded echoes, newest first.
+    pub fn snapshot(&self) -> Vec<Value> {
+        self.echoes.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Page of the recorded echoes, pages being numbered from 1. Pages past the last one are
+    /// empty.
+    pub fn page(&self, page: usize, per_page: usize) -> HistoryPage {
+        let page = page.max(1);
+        let per_page = per_page.max(1);
+        let echoes = self.echoes.lock().unwrap();
+
+        HistoryPage {
+            total: echoes.len(),
+            page,
+            per_page,
+            total_pages: echoes.len().div_ceil(per_page),
+            requests: echoes
+                .iter()
+                .skip((page - 1).saturating_mul(per_page))
+                .take(per_page)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+pub async fn history(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.history.snapshot())
+}
+
+pub async fn requests(query: web::Query<PageQuery>, state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.history.page(
+        query.page.unwrap_or(1),
+        query.per_page.unwrap_or(DEFAULT_PER_PAGE),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_evicts_oldest_echo() {
+        let history = RequestHistory::new(2);
+
+        for i in 0..3 {
+            history.record(json!(i));
+        }
+
+        assert_eq!(history.snapshot(), vec![json!(2), json!(1)]);
+    }
+
+    #[test]
+    fn test_record_is_disabled_without_capacity() {
+        let history = RequestHistory::new(0);
+
+        history.record(json!(1));
+
+        assert!(history.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_page() {
+        let history = RequestHistory::new(10);
+        for i in 0..5 {
+            history.record(json!(i));
+        }
+
+        assert_eq!(
+            history.page(2, 2),
+            HistoryPage {
+                total: 5,
+                page: 2,
+                per_page: 2,
+                total_pages: 3,
+                requests: vec![json!(2), json!(1)],
+            }
+        );
+        assert_eq!(history.page(3, 2).requests, vec![json!(0)]);
+        assert!(history.page(4, 2).requests.is_empty());
+        assert_eq!(history.page(0, 0).requests, vec![json!(4)]);
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+pub const HTML_CONTENT_TYPE: &str = "text/html";
+
+const INDENT: &str = "  ";
+
+const STYLE: &str = "body { background: #1e1e1e; color: #d4d4d4; } \
+    .key { color: #9cdcfe; } \
+    .string { color: #ce9178; } \
+    .number { color: #b5cea8; } \
+    .boolean { color: #569cd6; } \
+    .null { color: #c586c0; }";
+
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn span(out: &mut String, class: &str, text: &str) {
+    write!(out, "<span class=\"{}\">{}</span>", class, escape(text)).expect("writing to a String");
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::to_string(s).expect("strings always serialize")
+}
+
+fn highlight_value(out: &mut String, value: &Value, depth: usize) {
+    let indent = |depth: usize| INDENT.repeat(depth);
+
+    match value {
+        Value::Null => span(out, "null", "null"),
+        Value::Bool(b) => span(out, "boolean", &b.to_string()),
+        Value::Number(n) => span(out, "number", &n.to_string()),
+        Value::String(s) => span(out, "string", &json_string(s)),
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&indent(depth + 1));
+                highlight_value(out, item, depth + 1);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&indent(depth));
+            out.push(']');
+        }
+        Value::Object(fields) if fields.is_empty() => out.push_str("{}"),
+        Value::Object(fields) => {
+            out.push_str("{\n");
+            for (i, (key, item)) in fields.iter().enumerate() {
+                out.push_str(&indent(depth + 1));
+                span(out, "key", &json_string(key));
+                out.push_str(": ");
+                highlight_value(out, item, depth + 1);
+                out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&indent(depth));
+            out.push('}');
+        }
+    }
+}
+
+/// Renders a JSON value as a standalone HTML page, pretty-printed with each key and value in a
+/// `<span>` styled according to its type.
+pub fn highlight(value: &Value) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>");
+    out.push_str(STYLE);
+    out.push_str("</style>\n</head>\n<body>\n<pre>");
+    highlight_value(&mut out, value, 0);
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_highlight() {
+        let html = highlight(&json!({"name": "<b>", "count": 2, "ok": true, "none": null}));
+
+        assert!(html.contains("<span class=\"key\">&quot;name&quot;</span>: <span class=\"string\">&quot;&lt;b&gt;&quot;</span>"));
+        assert!(html.contains("<span class=\"number\">2</span>"));
+        assert!(html.contains("<span class=\"boolean\">true</span>"));
+        assert!(html.contains("<span class=\"null\">null</span>"));
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn test_highlight_nested() {
+        let mut out = String::new();
+        highlight_value(&mut out, &json!({"a": [1, {}], "b": []}), 0);
+
+        assert_eq!(
+            out,
+            "{\n  \
+               <span class=\"key\">&quot;a&quot;</span>: [\n    \
+                 <span class=\"number\">1</span>,\n    \
+                 {}\n  \
+               ],\n  \
+               <span class=\"key\">&quot;b&quot;</span>: []\n\
+             }"
+        );
+    }
+}
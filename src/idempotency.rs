@@ -0,0 +1,131 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+
+use crate::bounded::BoundedMap;
+use crate::dedup::{self, ContentHash};
+
+/// Reports when a structurally identical request was first seen.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct IdempotencyTracking {
+    /// Maximum number of fingerprints tracked, the oldest one is evicted first.
+    pub max_entries: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyInfo {
+    pub fingerprint: String,
+    pub first_seen: String,
+    pub age_secs: f64,
+}
+
+/// Fingerprint of the method, path and body of a request. JSON bodies are fingerprinted in their
+/// canonical form, so that key order and whitespace don't matter.
+pub fn fingerprint(method: &str, path: &str, body: &[u8], json: Option<&Value>) -> ContentHash {
+    match json {
+        Some(json) => dedup::content_hash(method, path, json.to_string().as_bytes()),
+        None => dedup::content_hash(method, path, body),
+    }
+}
+
+#[derive(Debug)]
+pub struct FingerprintTracker {
+    first_seen: Mutex<BoundedMap<ContentHash, DateTime<Utc>>>,
+}
+
+impl FingerprintTracker {
+    pub fn new(settings: &IdempotencyTracking) -> Result<Self, String> {
+        if settings.max_entries == 0 {
+            return Err("idempotency_tracking.max_entries must be positive".to_string());
+        }
+
+        Ok(Self {
+            first_seen: Mutex::new(BoundedMap::new(settings.max_entries)),
+        })
+    }
+
+    /// Records the fingerprint, if it's new, and reports when it was first seen.
+    pub fn observe(&self, fingerprint: ContentHash, now: DateTime<Utc>) -> IdempotencyInfo {
+        let mut entries = self.first_seen.lock().unwrap();
+
+        let first_seen = match entries.get(&fingerprint) {
+            Some(first_seen) => *first_seen,
+            None => {
+                entries.insert(fingerprint, now);
+                now
+            }
+        };
+        let age = now - first_seen;
+
+        IdempotencyInfo {
+            fingerprint: hex::encode(fingerprint),
+            first_seen: first_seen.to_rfc3339_opts(SecondsFormat::Millis, true),
+            age_secs: age.num_microseconds().unwrap_or(i64::MAX) as f64 / 1_000_000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use serde_json::json;
+
+    fn tracker(max_entries: usize) -> FingerprintTracker {
+        FingerprintTracker::new(&IdempotencyTracking { max_entries }).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_json_formatting() {
+        let a = json!({"id": 1, "name": "a"});
+        let b: Value = serde_json::from_str(r#"{ "name": "a",  "id": 1 }"#).unwrap();
+
+        assert_eq!(
+            fingerprint("POST", "/a", b"", Some(&a)),
+            fingerprint("POST", "/a", b"", Some(&b))
+        );
+        assert_ne!(
+            fingerprint("POST", "/a", b"x", None),
+            fingerprint("POST", "/a", b"y", None)
+        );
+    }
+
+    #[test]
+    fn test_observe_reports_age() {
+        let tracker = tracker(10);
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let hash = fingerprint("GET", "/", b"", None);
+
+        let first = tracker.observe(hash, now);
+        let second = tracker.observe(hash, now + Duration::milliseconds(1500));
+
+        assert_eq!(first.age_secs, 0.0);
+        assert_eq!(second.age_secs, 1.5);
+        assert_eq!(second.fingerprint, first.fingerprint);
+        assert_eq!(second.first_seen, "2024-05-01T12:00:00.000Z");
+    }
+
+    #[test]
+    fn test_observe_evicts_oldest_fingerprint() {
+        let tracker = tracker(2);
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let hashes: Vec<_> = ["/a", "/b", "/c"]
+            .iter()
+            .map(|path| fingerprint("GET", path, b"", None))
+            .collect();
+
+        for (i, hash) in hashes.iter().enumerate() {
+            tracker.observe(*hash, now + Duration::seconds(i as i64));
+        }
+
+        let later = now + Duration::seconds(10);
+        assert_eq!(tracker.observe(hashes[2], later).age_secs, 8.0);
+        assert_eq!(tracker.observe(hashes[0], later).age_secs, 0.0);
+    }
+
+    #[test]
+    fn test_new_rejects_empty_map() {
+        assert!(FingerprintTracker::new(&IdempotencyTracking { max_entries: 0 }).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use actix_web::{web::Bytes, Error};
+use futures_util::{stream, Stream, StreamExt};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::help::SpecialParam;
+
+pub const INFINITE_PARAM: &str = "__infinite";
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: INFINITE_PARAM,
+    value: "1",
+    description: "Streams JSON lines endlessly, until the client disconnects",
+}];
+
+/// Number of infinite bodies being streamed.
+static ACTIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a stream as active for as long as it's alive.
+struct ActiveStream;
+
+impl ActiveStream {
+    fn new() -> Self {
+        ACTIVE_STREAMS.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for ActiveStream {
+    fn drop(&mut self) {
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+pub fn active_streams() -> usize {
+    ACTIVE_STREAMS.load(Ordering::Relaxed)
+}
+
+pub fn enabled(value: &str) -> bool {
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
+
+/// Streams numbered JSON lines forever.
+///
+/// The stream isn't driven by a task of its own: actix polls it as the client reads, and drops it
+/// as soon as the client disconnects, so nothing is left running.
+pub fn stream(request_id: String) -> impl Stream<Item = Result<Bytes, Error>> {
+    let active = ActiveStream::new();
+
+    stream::iter(0u64..).map(move |seq| {
+        let _ = &active;
+        let line = json!({"seq": seq, "request_id": request_id});
+        Ok(Bytes::from(format!("{}\n", line)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_stream_yields_numbered_lines() {
+        let lines: Vec<_> = stream("abc".to_string())
+            .take(3)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(
+            lines,
+            vec![
+                Bytes::from_static(b"{\"request_id\":\"abc\",\"seq\":0}\n"),
+                Bytes::from_static(b"{\"request_id\":\"abc\",\"seq\":1}\n"),
+                Bytes::from_static(b"{\"request_id\":\"abc\",\"seq\":2}\n"),
+            ]
+        );
+    }
+}
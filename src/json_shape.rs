@@ -0,0 +1,90 @@
+use serde_json::Value;
+
+/// Number of nested objects and arrays, 0 for a scalar.
+pub fn depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        Value::Object(fields) => 1 + fields.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Number of nested objects and arrays of a serialized document, as [`depth`] gives once parsed.
+/// The bytes are scanned rather than parsed, so that the depth of documents nested deeper than
+/// `serde_json` can parse is known too.
+pub fn serialized_depth(bytes: &[u8]) -> usize {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &byte in bytes {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    max_depth
+}
+
+/// Number of values in the document, containers included.
+pub fn node_count(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(node_count).sum::<usize>(),
+        Value::Object(fields) => 1 + fields.values().map(node_count).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r#"1"#, 0)]
+    #[case(r#"{"a": {"b": [1]}, "c": []}"#, 3)]
+    #[case(r#"{"a": "[[{\"]]", "b": ["\\"]}"#, 2)]
+    fn test_serialized_depth(#[case] json: &str, #[case] expected: usize) {
+        assert_eq!(serialized_depth(json.as_bytes()), expected);
+        assert_eq!(depth(&serde_json::from_str(json).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_serialized_depth_beyond_parser_limit() {
+        let json = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+
+        assert_eq!(serialized_depth(json.as_bytes()), 1000);
+    }
+    use serde_json::json;
+
+    #[rstest]
+    #[case(json!(1), 0, 1)]
+    #[case(json!([]), 1, 1)]
+    #[case(json!({"a": 1, "b": [true, null]}), 2, 5)]
+    #[case(json!({"a": {"b": {"c": [1]}}}), 4, 5)]
+    fn test_shape(
+        #[case] value: Value,
+        #[case] expected_depth: usize,
+        #[case] expected_nodes: usize,
+    ) {
+        assert_eq!(depth(&value), expected_depth);
+        assert_eq!(node_count(&value), expected_nodes);
+    }
+}
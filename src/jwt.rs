@@ -0,0 +1,95 @@
+use actix_web::http::header::{HeaderMap, AUTHORIZATION};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A JWT sent as a bearer token, decoded without checking its signature.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JwtInfo {
+    pub header: Value,
+    pub claims: Value,
+    /// Whether the `exp` claim is past, `None` when it's missing or isn't a number.
+    pub expired: Option<bool>,
+}
+
+fn decode_part(part: &str) -> Option<Value> {
+    let bytes = URL_SAFE_NO_PAD.decode(part.trim_end_matches('=')).ok()?;
+
+    serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .filter(Value::is_object)
+}
+
+/// Decodes the JWT of an `Authorization: Bearer` header.
+pub fn decode(headers: &HeaderMap, now: DateTime<Utc>) -> Option<JwtInfo> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+
+    let mut parts = token.trim().split('.');
+    let (header, claims, _signature) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let header = decode_part(header)?;
+    let claims = decode_part(claims)?;
+    // Tokens must not be accepted on or after their expiration time (RFC 7519).
+    let expired = claims["exp"]
+        .as_f64()
+        .map(|exp| now.timestamp() as f64 >= exp);
+
+    Some(JwtInfo {
+        header,
+        claims,
+        expired,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        headers
+    }
+
+    fn token(claims: &str) -> String {
+        format!(
+            "{}.{}.c2ln",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[rstest]
+    #[case(r#"{"exp": 1714564799}"#, Some(true))]
+    #[case(r#"{"exp": 1714564800}"#, Some(true))]
+    #[case(r#"{"exp": 1714564801}"#, Some(false))]
+    #[case(r#"{"exp": "tomorrow"}"#, None)]
+    #[case(r#"{"sub": "me"}"#, None)]
+    fn test_decode_expired(#[case] claims: &str, #[case] expected: Option<bool>) {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let jwt = decode(&headers(&format!("Bearer {}", token(claims))), now).unwrap();
+
+        assert_eq!(jwt.header["alg"], "HS256");
+        assert_eq!(jwt.expired, expected);
+    }
+
+    #[rstest]
+    #[case("Basic dXNlcjpwYXNz")]
+    #[case("Bearer opaque-token")]
+    #[case("Bearer a.b.c")]
+    fn test_decode_ignores_non_jwt(#[case] authorization: &str) {
+        assert_eq!(decode(&headers(authorization), Utc::now()), None);
+    }
+}
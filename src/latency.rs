@@ -0,0 +1,98 @@
+use rand::{Rng, RngExt};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::help::SpecialParam;
+
+pub const PROFILE_PARAM: &str = "__profile";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: PROFILE_PARAM,
+    value: "<profile name>",
+    description: "Applies the named latency profile",
+}];
+
+/// A bundle of network conditions selectable per request with `__profile=<name>`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct LatencyProfile {
+    /// Base delay added before responding.
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Upper bound of the random delay added on top of `delay_ms`.
+    #[serde(default)]
+    pub jitter_ms: u64,
+    /// Probability, between 0 and 1, of answering with an error.
+    #[serde(default)]
+    pub error_rate: f64,
+}
+
+impl LatencyProfile {
+    pub fn sample_delay<R: Rng>(&self, rng: &mut R) -> Duration {
+        let jitter = if self.jitter_ms > 0 {
+            rng.random_range(0..=self.jitter_ms)
+        } else {
+            0
+        };
+
+        Duration::from_millis(self.delay_ms + jitter)
+    }
+
+    pub fn sample_error<R: Rng>(&self, rng: &mut R) -> bool {
+        rng.random_bool(self.error_rate.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_sample_delay_stays_within_jitter() {
+        let profile = LatencyProfile {
+            delay_ms: 100,
+            jitter_ms: 50,
+            error_rate: 0.0,
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..1000 {
+            let delay = profile.sample_delay(&mut rng);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn test_sample_delay_without_jitter_is_constant() {
+        let profile = LatencyProfile {
+            delay_ms: 100,
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+
+        assert_eq!(profile.sample_delay(&mut rng), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_sample_error_follows_rate() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let never = LatencyProfile {
+            error_rate: 0.0,
+            ..Default::default()
+        };
+        let always = LatencyProfile {
+            error_rate: 1.0,
+            ..Default::default()
+        };
+        let out_of_range = LatencyProfile {
+            error_rate: 3.0,
+            ..Default::default()
+        };
+
+        assert!((0..100).all(|_| !never.sample_error(&mut rng)));
+        assert!((0..100).all(|_| always.sample_error(&mut rng)));
+        assert!((0..100).all(|_| out_of_range.sample_error(&mut rng)));
+    }
+}
@@ -0,0 +1,288 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::{LevelFilter, Log, Metadata, Record};
+use log_rs::LogConfig;
+use serde::Deserialize;
+use serde_json::json;
+use std::io::Write;
+use std::sync::Mutex;
+
+use crate::otel::RequestLogFormat;
+
+/// Target of the OpenTelemetry request log records, written as is on stdout.
+pub const REQUESTS_TARGET: &str = "catchall_api::requests";
+
+/// Format of the application logs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines, as formatted by `log_rs`, or on stderr alongside the OpenTelemetry
+    /// request log records.
+    #[default]
+    Pretty,
+    /// One JSON object per line on stderr, for log ingestion.
+    Json,
+}
+
+/// Writes each record on its own line, as formatted by `format`.
+struct LineLogger<W> {
+    level: LevelFilter,
+    writer: Mutex<W>,
+    format: fn(&Record, DateTime<Utc>) -> String,
+}
+
+/// JSON object with the `timestamp`, `level`, `module` and `message` of the record.
+fn json_line(record: &Record, timestamp: DateTime<Utc>) -> String {
+    let line = json!({
+        "timestamp": timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "level": record.level().as_str(),
+        "module": record.module_path().unwrap_or(record.target()),
+        "message": record.args().to_string(),
+    });
+
+    format!("{}\n", line)
+}
+
+fn pretty_line(record: &Record, timestamp: DateTime<Utc>) -> String {
+    format!(
+        "{} {:<5} {}: {}\n",
+        timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+        record.level(),
+        record.module_path().unwrap_or(record.target()),
+        record.args()
+    )
+}
+
+/// The message alone, for records that are already formatted.
+fn raw_line(record: &Record, _timestamp: DateTime<Utc>) -> String {
+    format!("{}\n", record.args())
+}
+
+impl<W: Write + Send> Log for LineLogger<W> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = (self.format)(record, Utc::now());
+        let _ = self.writer.lock().unwrap().write_all(line.as_bytes());
+    }
+
+    fn flush(&self) {
+        let _ = self.writer.lock().unwrap().flush();
+    }
+}
+
+/// Sends the records of [`REQUESTS_TARGET`] to `requests`, when set, and the others to `app`.
+struct Dispatch<A, R> {
+    app: A,
+    requests: Option<R>,
+}
+
+impl<A: Log, R: Log> Dispatch<A, R> {
+    fn logger_for(&self, target: &str) -> &dyn Log {
+        match &self.requests {
+            Some(requests) if target == REQUESTS_TARGET => requests,
+            _ => &self.app,
+        }
+    }
+}
+
+impl<A: Log, R: Log> Log for Dispatch<A, R> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger_for(metadata.target()).enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.logger_for(record.target()).log(record);
+    }
+
+    fn flush(&self) {
+        self.app.flush();
+        if let Some(requests) = &self.requests {
+            requests.flush();
+        }
+    }
+}
+
+/// Level of the records logged, from `RUST_LOG` (e.g. `warn`), `info` by default.
+fn level_from_env() -> LevelFilter {
+    std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(LevelFilter::Info)
+}
+
+/// Sets up the application logger. The OpenTelemetry request log records go through the same
+/// logger, so that they can't interleave with the other lines, and are filtered alike.
+pub fn init(format: LogFormat, requests: RequestLogFormat) {
+    let format = match format {
+        LogFormat::Pretty if !requests.otel() => {
+            return log_rs::init(
+                LogConfig::from_env().expect("Unable to initialize log config from env"),
+            )
+        }
+        LogFormat::Pretty => pretty_line,
+        LogFormat::Json => json_line,
+    };
+
+    let level = level_from_env();
+    let logger = Dispatch {
+        app: LineLogger {
+            level,
+            writer: Mutex::new(std::io::stderr()),
+            format,
+        },
+        requests: requests.otel().then(|| LineLogger {
+            level,
+            writer: Mutex::new(std::io::stdout()),
+            format: raw_line,
+        }),
+    };
+    log::set_boxed_logger(Box::new(logger)).expect("logger initialized once");
+    log::set_max_level(level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use log::Level;
+    use serde_json::Value;
+
+    fn buffer_logger(format: fn(&Record, DateTime<Utc>) -> String) -> LineLogger<Vec<u8>> {
+        LineLogger {
+            level: LevelFilter::Info,
+            writer: Mutex::new(Vec::new()),
+            format,
+        }
+    }
+
+    fn output(logger: LineLogger<Vec<u8>>) -> String {
+        String::from_utf8(logger.writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_json_line() {
+        let timestamp = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let line = json_line(
+            &Record::builder()
+                .level(Level::Warn)
+                .module_path(Some("catchall_api::stats"))
+                .args(format_args!("evicted {} paths", 3))
+                .build(),
+            timestamp,
+        );
+
+        let line: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(
+            line,
+            json!({
+                "timestamp": "2024-05-01T12:00:00.000Z",
+                "level": "WARN",
+                "module": "catchall_api::stats",
+                "message": "evicted 3 paths",
+            })
+        );
+    }
+
+    #[test]
+    fn test_pretty_line() {
+        let timestamp = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let line = pretty_line(
+            &Record::builder()
+                .level(Level::Info)
+                .module_path(Some("catchall_api"))
+                .args(format_args!("Shutdown complete"))
+                .build(),
+            timestamp,
+        );
+
+        assert_eq!(
+            line,
+            "2024-05-01T12:00:00.000Z INFO  catchall_api: Shutdown complete\n"
+        );
+    }
+
+    #[test]
+    fn test_json_logger_writes_one_object_per_line() {
+        let logger = buffer_logger(json_line);
+
+        for (level, message) in [
+            (Level::Error, "first"),
+            (Level::Debug, "filtered"),
+            (Level::Info, "second\nline"),
+        ] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .target("catchall_api")
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        }
+
+        let output = output(logger);
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["message"], "first");
+        assert_eq!(lines[0]["module"], "catchall_api");
+        assert_eq!(lines[1]["message"], "second\nline");
+    }
+
+    #[test]
+    fn test_dispatch_writes_request_records_as_is() {
+        let logger = Dispatch {
+            app: buffer_logger(json_line),
+            requests: Some(buffer_logger(raw_line)),
+        };
+
+        for (target, message) in [
+            (REQUESTS_TARGET, r#"{"body":"GET /"}"#),
+            ("catchall_api", "Shutdown complete"),
+        ] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Info)
+                    .target(target)
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        }
+
+        assert_eq!(output(logger.requests.unwrap()), "{\"body\":\"GET /\"}\n");
+        let app: Value = serde_json::from_str(&output(logger.app)).unwrap();
+        assert_eq!(app["message"], "Shutdown complete");
+    }
+
+    #[test]
+    fn test_dispatch_filters_request_records() {
+        let logger = Dispatch {
+            app: buffer_logger(json_line),
+            requests: Some(LineLogger {
+                level: LevelFilter::Warn,
+                writer: Mutex::new(Vec::new()),
+                format: raw_line,
+            }),
+        };
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .target(REQUESTS_TARGET)
+                .args(format_args!("{{}}"))
+                .build(),
+        );
+
+        assert_eq!(output(logger.requests.unwrap()), "");
+        assert_eq!(output(logger.app), "");
+    }
+}
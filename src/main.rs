@@ -1,17 +1,163 @@
-use actix_web::{middleware::Logger, web, App, HttpRequest, HttpServer, Responder, Result};
+mod accept;
+mod batch;
+mod body_limit;
+mod bounded;
+mod captive_portal;
+mod challenge;
+mod connection_limit;
+mod consistency;
+mod cookies;
+mod deadline;
+mod dedup;
+mod degradation;
+mod delay;
+mod dependencies;
+mod diff;
+mod envelope;
+mod error_injection;
+mod fields;
+mod forwarded;
+mod fuzz;
+mod geoip;
+mod health;
+mod help;
+mod history;
+mod html;
+mod idempotency;
+mod infinite;
+mod json_shape;
+mod jwt;
+mod latency;
+mod logging;
+mod malformed;
+mod metrics;
+mod msgpack;
+mod multipart;
+mod oauth;
+mod otel;
+mod pagination;
+mod path_value;
+mod paywall;
+mod protobuf;
+mod redirect_loop;
+mod rename;
+mod request_id;
+mod retry_after;
+mod rollout;
+mod settings;
+mod signing;
+mod slow_read;
+mod split;
+mod stable;
+mod stats;
+mod status;
+mod tls;
+mod trailer;
+mod transform;
+mod unix_socket;
+mod xml;
+
+use accept::MediaRange;
+use actix_cors::Cors;
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    error::ErrorInternalServerError,
+    http::{
+        header::{
+            ContentEncoding, ContentType, HeaderName, HeaderValue, ACCEPT, ALLOW, CONNECTION,
+            CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_TYPE, EXPECT, IF_MATCH,
+            IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_UNMODIFIED_SINCE, LINK, LOCATION, UPGRADE,
+            WWW_AUTHENTICATE,
+        },
+        Method, StatusCode,
+    },
+    middleware::{from_fn, Condition, Logger},
+    rt::{
+        self,
+        signal::unix::{signal, SignalKind},
+        time::{sleep, timeout},
+    },
+    web, App, Error, HttpMessage, HttpRequest, HttpResponse, HttpServer, Result,
+};
 use base64::{engine::general_purpose::STANDARD as b64engine, Engine as _};
-use config::{Config, ConfigError};
+use batch::BatchWindow;
+use body_limit::ContentTypeLimits;
+use challenge::CHALLENGE_PARAM;
+use connection_limit::ConnectionLimiter;
+use consistency::EventualStore;
+use cookies::CookieInfo;
+use dedup::ResponseCache;
+use degradation::DegradationWindow;
+use delay::DELAY_PARAM;
+use dependencies::DependencyCall;
+use diff::JsonDiff;
+use envelope::{
+    CLOUDEVENTS_CONTENT_TYPE, DOWNLOAD_CONTENT_DISPOSITION, DOWNLOAD_CONTENT_TYPE, ECHO_AMQP,
+    ECHO_APIGW, ECHO_DOWNLOAD, ECHO_EVENT, ECHO_LOG_EVENT, ECHO_NGINX, ECHO_PARAM,
+};
+use error_injection::ErrorInjector;
+use fields::{FieldFilter, EXCLUDE_PARAM, FIELDS_PARAM};
+use flate2::{write::GzEncoder, Compression};
+use futures_util::future;
+use fuzz::HeaderFuzzer;
+use geoip::{GeoInfo, GeoLookup, MaxMindLookup};
+use help::HELP_PATH;
+use history::{RequestHistory, HISTORY_PATH, REQUESTS_PATH};
+use html::HTML_CONTENT_TYPE;
+use idempotency::{FingerprintTracker, IdempotencyInfo};
+use infinite::{INFINITE_PARAM, NDJSON_CONTENT_TYPE};
+use jwt::JwtInfo;
+use latency::PROFILE_PARAM;
 use log::info;
-use log_rs::LogConfig;
+use malformed::{BAD_JSON, BAD_PARAM};
+use metrics::{RequestMetrics, METRICS_PATH};
+use msgpack::MSGPACK_CONTENT_TYPE;
+use multipart::MultipartInfo;
+use oauth::OAUTH_TOKEN_PATH;
+use pagination::PAGINATE_PARAM;
+use percent_encoding::percent_decode_str;
+use protobuf::{ProtobufEncoder, PROTOBUF_CONTENT_TYPE};
+use redirect_loop::LOOP_PARAM;
+use request_id::X_REQUEST_ID;
+use rollout::VersionRoller;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use settings::{get_config, AppSettings};
+use signing::{Ed25519Signer, ED25519_SIGNATURE_HEADER, PUBLIC_KEY_HEADER, SIGNATURE_HEADER};
+use split::{SPLIT_CHUNKS, SPLIT_PARAM};
+use stable::STABLE_PARAM;
+use stats::{PathStatsStore, STATS_PATH};
+use status::STATUS_PARAM;
 use std::collections::HashMap;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::io::Write;
+use std::path::Path;
+use std::pin::pin;
+use std::time::{Duration, Instant};
+use trailer::TRAILER_PARAM;
+use transform::JqTransform;
+use xml::XML_CONTENT_TYPE;
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const TEXT_PLAIN_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+const GZIP_BASE64_ENCODING: &str = "gzip+base64";
+const TOML_CONTENT_TYPES: [&str; 2] = ["application/toml", "text/toml"];
+const INI_CONTENT_TYPE: &str = "text/ini";
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+/// Formats the echo can be serialized into, JSON first as the default one.
+const SERIALIZED_CONTENT_TYPES: [&str; 3] =
+    [JSON_CONTENT_TYPE, XML_CONTENT_TYPE, MSGPACK_CONTENT_TYPE];
+/// Methods answered by the catchall, as advertised to `OPTIONS` requests.
+const ALLOWED_METHODS: &str = "GET, POST, PUT, PATCH, DELETE, OPTIONS, HEAD";
+/// Prefix of the request headers mirrored as response headers, as lowercased by actix.
+const MIRROR_HEADER_PREFIX: &str = "x-echo-";
 
 #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 struct ClientInfo {
     remote_ip: Option<String>,
-    port: u16,
+    /// Port of the peer, unknown on a unix socket.
+    port: Option<u16>,
+    geo: Option<GeoInfo>,
 }
 
 #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -20,383 +166,4835 @@ struct UrlInfo {
     hostname: String,
     port: u16,
     path: String,
+    path_segments: Vec<String>,
+    /// The URL of the request as sent, e.g. `http://localhost:8080/foo?x=1`.
+    full_uri: String,
+    /// Scheme forwarded by a proxy, which `scheme` then reflects.
+    forwarded_proto: Option<String>,
+    /// Host forwarded by a proxy, which `hostname` and `port` then reflect.
+    forwarded_host: Option<String>,
+}
+
+/// Validators of a conditional request, as sent by the client.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ConditionalInfo {
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+    if_unmodified_since: Option<String>,
+}
+
+/// How the format of the response was picked from the `Accept` header.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct NegotiationInfo {
+    accept: Option<String>,
+    /// Media types the echo can be served as, in the order they are considered.
+    supported: Vec<String>,
+    chosen: String,
+    reason: String,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ServerInfo {
+    version: String,
+}
+
+/// Time spent processing the request, injected delays included, against the configured budget.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct SloInfo {
+    budget_ms: u64,
+    actual_ms: u64,
+    within_budget: bool,
+}
+
+impl SloInfo {
+    fn new(budget_ms: u64, elapsed: Duration) -> Self {
+        let actual_ms = elapsed.as_millis() as u64;
+
+        Self {
+            budget_ms,
+            actual_ms,
+            within_budget: actual_ms <= budget_ms,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Body {
+    json: Option<Value>,
+    /// Number of nested objects and arrays in `json`.
+    json_depth: Option<usize>,
+    /// Number of values in `json`, containers included.
+    json_node_count: Option<usize>,
+    raw: String,
+    /// The body decoded as UTF-8, when it's valid UTF-8.
+    text: Option<String>,
+    /// `gzip+base64` when `raw` holds the gzip-compressed body, plain base64 otherwise.
+    raw_encoding: Option<String>,
+    multipart: Option<MultipartInfo>,
+    toml: Option<Value>,
+    /// Keys by section, with the keys set before the first section under `""`.
+    ini: Option<HashMap<String, HashMap<String, String>>>,
+    /// Fields of `application/x-www-form-urlencoded` bodies, with every value of repeated ones.
+    form: Option<HashMap<String, Vec<String>>>,
+    /// `Content-Encoding` the body was decoded from before being echoed.
+    decoded_with: Option<String>,
+    /// Whether only the first `max_body_bytes` of the body are in `raw`, in which case `json` isn't
+    /// parsed.
+    truncated: bool,
+    /// Size of the body in bytes, as received.
+    total_size: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct CatchallResponse {
+    request_id: String,
+    /// When the request was handled, in UTC with millisecond precision.
+    received_at: String,
+    method: String,
+    /// Protocol version of the request, e.g. `HTTP/1.1` or `HTTP/2.0`.
+    http_version: String,
+    path: String,
+    client: ClientInfo,
+    url: UrlInfo,
+    /// Values of each header, in the order they were received.
+    headers: HashMap<String, Vec<String>>,
+    accept: Vec<MediaRange>,
+    /// Value of each cookie sent in the `Cookie` headers.
+    cookies: HashMap<String, String>,
+    cookie_details: Option<Vec<CookieInfo>>,
+    jwt: Option<JwtInfo>,
+    conditional: ConditionalInfo,
+    /// Values of each query parameter, in the order they were received.
+    query_params: HashMap<String, Vec<String>>,
+    body: Body,
+    /// Differences between the body and the baseline configured for the path.
+    diff: Option<JsonDiff>,
+    server: Option<ServerInfo>,
+    slo: Option<SloInfo>,
+    /// Whether the echo of an identical earlier request was returned, when de-duplicating.
+    cache_hit: Option<bool>,
+    idempotency: Option<IdempotencyInfo>,
+    /// Simulated downstream calls the request was processed across.
+    dependencies: Option<Vec<DependencyCall>>,
+    negotiation: Option<NegotiationInfo>,
+}
+
+impl CatchallResponse {
+    /// First value of a request header, by its lowercase name.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.first().map(String::as_str)
+    }
+
+    /// Last value of a query parameter, so that it can be overridden by appending to the URL.
+    fn query_param(&self, name: &str) -> Option<&str> {
+        self.query_params.get(name)?.last().map(String::as_str)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct JsonErrorResponse {
+    error: String,
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct MissingQueryParamsResponse {
+    error: String,
+    missing: Vec<String>,
+}
+
+#[derive(Debug)]
+struct AppState {
+    settings: AppSettings,
+    protobuf: Option<ProtobufEncoder>,
+    geoip: Option<Box<dyn GeoLookup>>,
+    connection_limiter: Option<ConnectionLimiter>,
+    path_stats: PathStatsStore,
+    metrics: RequestMetrics,
+    history: RequestHistory,
+    header_fuzzer: Option<HeaderFuzzer>,
+    error_injector: ErrorInjector,
+    version_roller: Option<VersionRoller>,
+    degradation_window: Option<DegradationWindow>,
+    response_cache: Option<ResponseCache>,
+    eventual_store: Option<EventualStore>,
+    batch_window: Option<BatchWindow>,
+    fingerprint_tracker: Option<FingerprintTracker>,
+    body_baselines: HashMap<String, Value>,
+    jq_transform: Option<JqTransform>,
+    ed25519_signer: Option<Ed25519Signer>,
+    content_type_limits: ContentTypeLimits,
+    static_response: Option<web::Bytes>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new(AppSettings::default()).expect("valid default settings")
+    }
+}
+
+impl AppState {
+    fn new(settings: AppSettings) -> Result<Self, String> {
+        let protobuf = match (
+            &settings.protobuf_descriptor_path,
+            &settings.protobuf_message,
+        ) {
+            (Some(path), Some(message)) => {
+                Some(ProtobufEncoder::from_file(path, message).map_err(|e| e.to_string())?)
+            }
+            (None, None) => None,
+            _ => {
+                return Err(
+                    "protobuf_descriptor_path and protobuf_message must be set together"
+                        .to_string(),
+                )
+            }
+        };
+
+        let geoip = match &settings.geoip_db_path {
+            Some(path) => {
+                let lookup = MaxMindLookup::open(path, settings.geoip_asn_db_path.as_deref())
+                    .map_err(|e| format!("unable to open GeoIP database: {}", e))?;
+                Some(Box::new(lookup) as Box<dyn GeoLookup>)
+            }
+            None => None,
+        };
+
+        let connection_limiter = settings.max_connections_per_ip.map(ConnectionLimiter::new);
+        let path_stats = PathStatsStore::new(settings.stats_max_paths);
+        let history = RequestHistory::new(settings.history_size);
+        let header_fuzzer = settings
+            .fuzz_headers
+            .as_ref()
+            .map(HeaderFuzzer::new)
+            .transpose()?;
+        let error_injector = ErrorInjector::new(
+            &settings.path_error_rates,
+            settings.error_rate,
+            settings.error_seed,
+        );
+        let version_roller = settings
+            .rolling_versions
+            .as_ref()
+            .map(VersionRoller::new)
+            .transpose()?;
+        let degradation_window = settings
+            .health_window
+            .as_ref()
+            .map(DegradationWindow::new)
+            .transpose()?;
+        let response_cache = settings.dedup_cache.as_ref().map(ResponseCache::new);
+        let eventual_store = settings
+            .consistency_delay_ms
+            .map(|delay_ms| EventualStore::new(delay_ms, settings.consistency_max_paths));
+        let batch_window = settings
+            .batching
+            .as_ref()
+            .map(BatchWindow::new)
+            .transpose()?;
+        let fingerprint_tracker = settings
+            .idempotency_tracking
+            .as_ref()
+            .map(FingerprintTracker::new)
+            .transpose()?;
+        let body_baselines = settings
+            .body_baselines
+            .iter()
+            .map(|(path, baseline)| {
+                serde_json::from_str(baseline)
+                    .map(|baseline| (path.clone(), baseline))
+                    .map_err(|e| format!("invalid body baseline for {}: {}", path, e))
+            })
+            .collect::<Result<_, _>>()?;
+        let jq_transform = settings
+            .jq_transform
+            .as_deref()
+            .map(JqTransform::new)
+            .transpose()?;
+        let ed25519_signer = settings
+            .response_signing_ed25519_key
+            .as_deref()
+            .map(Ed25519Signer::new)
+            .transpose()?;
+        let content_type_limits = ContentTypeLimits::new(&settings.content_type_limits);
+        let static_response =
+            match (
+                &settings.static_response_body,
+                &settings.static_response_file,
+            ) {
+                (Some(body), None) => Some(web::Bytes::from(body.clone())),
+                (None, Some(path)) => Some(web::Bytes::from(std::fs::read(path).map_err(|e| {
+                    format!("unable to read static response file {}: {}", path, e)
+                })?)),
+                (None, None) => None,
+                (Some(_), Some(_)) => {
+                    return Err(
+                        "static_response_body and static_response_file are mutually exclusive"
+                            .to_string(),
+                    )
+                }
+            };
+
+        Ok(Self {
+            settings,
+            protobuf,
+            geoip,
+            connection_limiter,
+            path_stats,
+            metrics: RequestMetrics::default(),
+            history,
+            header_fuzzer,
+            error_injector,
+            version_roller,
+            degradation_window,
+            response_cache,
+            eventual_store,
+            batch_window,
+            fingerprint_tracker,
+            body_baselines,
+            jq_transform,
+            ed25519_signer,
+            content_type_limits,
+            static_response,
+        })
+    }
+}
+
+async fn handler(
+    req: HttpRequest,
+    bytes: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let method = req.method().clone();
+
+    let result = match state.settings.response_timeout_ms {
+        // For streamed responses, only the time until the response starts is capped.
+        Some(response_timeout_ms) => timeout(
+            Duration::from_millis(response_timeout_ms),
+            respond(req, bytes, state.clone()),
+        )
+        .await
+        .unwrap_or_else(|_| Ok(HttpResponse::GatewayTimeout().finish())),
+        None => respond(req, bytes, state.clone()).await,
+    };
+
+    let status = match &result {
+        Ok(resp) => resp.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    state.metrics.record(method.as_str(), status);
+
+    result
+}
+
+async fn respond(
+    req: HttpRequest,
+    bytes: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let started = Instant::now();
+    let query_params = get_query_params(req.query_string());
+
+    // actix-http answers `Expect: 100-continue` with an interim `100 Continue` before the request
+    // reaches the handler, so the rejection can only be sent as the final response.
+    if state.settings.reject_expectations && req.headers().contains_key(EXPECT) {
+        return Ok(HttpResponse::ExpectationFailed().finish());
+    }
+
+    if let Some(authority) = path_value::find(&state.settings.blocked_paths, req.path()) {
+        return Ok(
+            HttpResponse::build(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS)
+                .insert_header((LINK, format!("<{}>; rel=\"blocked-by\"", authority)))
+                .json(ErrorResponse {
+                    error: "unavailable for legal reasons".to_string(),
+                }),
+        );
+    }
+
+    if let Some(protocol) = path_value::find(&state.settings.upgrade_required_paths, req.path()) {
+        return Ok(HttpResponse::build(StatusCode::UPGRADE_REQUIRED)
+            .insert_header((UPGRADE, protocol.as_str()))
+            .insert_header((CONNECTION, "Upgrade"))
+            .json(ErrorResponse {
+                error: format!("upgrade to {} required", protocol),
+            }));
+    }
+
+    if let Some(paywall) = state
+        .settings
+        .paywall
+        .as_ref()
+        .filter(|paywall| paywall.requires_payment(req.headers(), req.path()))
+    {
+        return Ok(
+            HttpResponse::build(StatusCode::PAYMENT_REQUIRED).json(paywall.payment_required())
+        );
+    }
+
+    if let Some(portal) = state
+        .settings
+        .captive_portal
+        .as_ref()
+        .filter(|portal| portal.requires_login(req.headers(), req.path()))
+    {
+        return Ok(
+            HttpResponse::build(StatusCode::NETWORK_AUTHENTICATION_REQUIRED)
+                .content_type(HTML_CONTENT_TYPE)
+                .body(portal.login_page()),
+        );
+    }
+
+    if state.settings.strict_json && is_json(&req) && !bytes.is_empty() {
+        if let Err(e) = serde_json::from_slice::<Value>(&bytes) {
+            return Ok(HttpResponse::BadRequest().json(JsonErrorResponse {
+                error: e.to_string(),
+                line: e.line(),
+                column: e.column(),
+            }));
+        }
+    }
+
+    if let Some(max_depth) = state.settings.max_json_depth.filter(|_| is_json(&req)) {
+        // Measured on the bytes, as serde_json can't parse the deepest documents.
+        let depth = json_shape::serialized_depth(&bytes);
+        if depth > max_depth {
+            return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+                error: format!(
+                    "JSON body is nested {} levels deep, the maximum is {}",
+                    depth, max_depth
+                ),
+            }));
+        }
+    }
+
+    if bytes.len() > state.content_type_limits.limit(req.content_type()) {
+        return Ok(HttpResponse::PayloadTooLarge().finish());
+    }
+
+    if let Some(spec) = query_params.get(TRAILER_PARAM).and_then(|v| v.last()) {
+        return Ok(match trailer::parse(spec) {
+            Some((name, _)) => HttpResponse::NotImplemented().json(ErrorResponse {
+                error: trailer::unsupported_reason(&name),
+            }),
+            None => HttpResponse::BadRequest().json(ErrorResponse {
+                error: format!("invalid {}, expected Name:value", TRAILER_PARAM),
+            }),
+        });
+    }
+
+    let missing: Vec<_> = state
+        .settings
+        .required_query_params
+        .iter()
+        .filter(|param| !query_params.contains_key(*param))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(MissingQueryParamsResponse {
+            error: "missing required query parameters".to_string(),
+            missing,
+        }));
+    }
+
+    let method = req.method();
+    let path = req.path();
+    let mut client_info = get_client(&req);
+    if let Some(geoip) = &state.geoip {
+        client_info.geo = client_info
+            .remote_ip
+            .as_ref()
+            .and_then(|ip| ip.parse().ok())
+            .and_then(|ip| geoip.lookup(ip));
+    }
+    let url_info = get_url_info(&req);
+    let headers = get_headers(&req);
+    let mut body = get_body(bytes.clone(), req.content_type(), &state.settings);
+    if state.settings.parse_multipart {
+        body.multipart = get_multipart(&req, &bytes);
+    }
+    if state.settings.parse_toml {
+        body.toml = get_toml(&req, &bytes);
+    }
+    if state.settings.parse_ini {
+        body.ini = get_ini(&req, &bytes);
+    }
+    body.form = get_form(&req, &bytes);
+    body.decoded_with = get_decoded_with(&req);
+    if let Some(store) = &state.eventual_store {
+        if method == Method::POST {
+            if let Some(json) = &body.json {
+                store.write(path, json.clone(), Instant::now());
+            }
+        } else if method == Method::GET {
+            match store.read(path, Instant::now()) {
+                consistency::Read::Unwritten => {}
+                consistency::Read::Pending => return Ok(HttpResponse::NotFound().finish()),
+                consistency::Read::Visible(value) => return Ok(HttpResponse::Ok().json(value)),
+            }
+        }
+    }
+    let diff = state
+        .body_baselines
+        .get(path)
+        .zip(body.json.as_ref())
+        .map(|(baseline, json)| diff::diff(baseline, json));
+
+    let mut resp = CatchallResponse {
+        request_id: request_id::from_headers_or_generate(
+            req.headers(),
+            state.settings.request_id_format,
+        ),
+        received_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        method: method.to_string(),
+        http_version: format!("{:?}", req.version()),
+        path: path.to_string(),
+        client: client_info,
+        url: url_info,
+        headers,
+        accept: get_accept(&req),
+        cookies: get_cookies(&req),
+        cookie_details: state
+            .settings
+            .cookie_details
+            .then(|| cookies::parse(req.headers())),
+        jwt: state
+            .settings
+            .decode_jwt
+            .then(|| jwt::decode(req.headers(), chrono::Utc::now()))
+            .flatten(),
+        conditional: get_conditional(&req),
+        query_params,
+        body,
+        diff,
+        server: state.version_roller.as_ref().map(|roller| ServerInfo {
+            version: roller.next().to_string(),
+        }),
+        slo: None,
+        cache_hit: None,
+        idempotency: None,
+        dependencies: None,
+        negotiation: None,
+    };
+
+    if let Some(cache) = &state.response_cache {
+        let hash = dedup::content_hash(method.as_str(), path, &bytes);
+        let now = Instant::now();
+        match cache
+            .get(&hash, now)
+            .and_then(|echo| serde_json::from_value(echo).ok())
+        {
+            Some(cached) => {
+                resp = cached;
+                resp.cache_hit = Some(true);
+            }
+            None => {
+                resp.cache_hit = Some(false);
+                let echo = serde_json::to_value(&resp).map_err(ErrorInternalServerError)?;
+                cache.insert(hash, echo, now);
+            }
+        }
+    }
+
+    if let Some(tracker) = &state.fingerprint_tracker {
+        let fingerprint =
+            idempotency::fingerprint(method.as_str(), path, &bytes, resp.body.json.as_ref());
+        resp.idempotency = Some(tracker.observe(fingerprint, chrono::Utc::now()));
+    }
+
+    let log_format = state.settings.request_log_format;
+    if log_format.pretty() {
+        info!(
+            "{} {}\n{}",
+            method,
+            path,
+            serde_json::to_string_pretty(&resp).expect("Error dumping resp to json")
+        );
+    }
+    if log_format.otel() {
+        let mut record = otel::log_record(&resp, chrono::Utc::now());
+        if state.settings.trace_body_attributes {
+            otel::add_body_attributes(&mut record, &bytes, state.settings.trace_body_preview_bytes);
+        }
+        info!(target: logging::REQUESTS_TARGET, "{}", record);
+    }
+
+    if let Some(body) = &state.static_response {
+        return Ok(HttpResponse::Ok()
+            .insert_header((X_REQUEST_ID, resp.request_id.as_str()))
+            .content_type(state.settings.static_response_content_type.as_str())
+            .body(body.clone()));
+    }
+
+    let mut status = resp
+        .query_param(STATUS_PARAM)
+        .and_then(status::parse)
+        .unwrap_or(StatusCode::OK);
+
+    // Every delay of the request is added up before waiting, to be checked against its deadline.
+    let mut delay = Duration::ZERO;
+
+    if let Some(profile) = resp
+        .query_param(PROFILE_PARAM)
+        .and_then(|name| state.settings.latency_profiles.get(name))
+    {
+        let mut rng = rand::rng();
+        delay += profile.sample_delay(&mut rng);
+        if profile.sample_error(&mut rng) {
+            status = StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    }
+
+    if let Some(requested) = resp
+        .query_param(DELAY_PARAM)
+        .and_then(|ms| delay::parse(ms, state.settings.max_delay_ms))
+    {
+        delay += requested;
+    }
+
+    if let Some(per_kb_ms) = state.settings.delay_per_kb_ms {
+        delay += delay::for_body_size(bytes.len(), per_kb_ms, state.settings.max_delay_ms);
+    }
+
+    if let Some(chain) = &state.settings.dependency_chain {
+        delay += chain.total_delay(state.settings.max_delay_ms);
+        resp.dependencies = Some(chain.calls());
+    }
+
+    // No point in making the client wait if the response would come after its deadline, or in
+    // responding at all once it passed.
+    if deadline::remaining(req.headers(), chrono::Utc::now())
+        .is_some_and(|left| left.is_zero() || delay >= left)
+    {
+        return Ok(HttpResponse::GatewayTimeout().finish());
+    }
+
+    if !delay.is_zero() {
+        sleep(delay).await;
+    }
+
+    if state.error_injector.should_fail(&resp.path) {
+        status = StatusCode::INTERNAL_SERVER_ERROR;
+    }
+
+    if state
+        .degradation_window
+        .as_ref()
+        .is_some_and(|window| !window.next_is_healthy())
+    {
+        status = StatusCode::SERVICE_UNAVAILABLE;
+    }
+
+    resp.slo = state
+        .settings
+        .slo_budget_ms
+        .map(|budget_ms| SloInfo::new(budget_ms, started.elapsed()));
+
+    let negotiation = negotiate(&req, &state);
+    let format = negotiation.chosen.clone();
+    if state.settings.negotiation_trace {
+        resp.negotiation = Some(negotiation);
+    }
+
+    let challenge = resp
+        .query_param(CHALLENGE_PARAM)
+        .and_then(|scheme| challenge::www_authenticate(scheme, &state.settings.challenge_realm));
+    if challenge.is_some() {
+        status = StatusCode::UNAUTHORIZED;
+    }
+
+    let redirect = resp
+        .query_param(LOOP_PARAM)
+        .and_then(|n| n.parse::<u32>().ok())
+        .filter(|n| *n > 0)
+        .map(|n| redirect_loop::next_location(&resp.path, req.query_string(), n - 1));
+    if redirect.is_some() {
+        status = StatusCode::FOUND;
+    }
+
+    state
+        .history
+        .record(serde_json::to_value(&resp).map_err(ErrorInternalServerError)?);
+
+    let mut builder = HttpResponse::build(status);
+    builder.insert_header((X_REQUEST_ID, resp.request_id.as_str()));
+
+    if state.settings.mirror_echo_headers {
+        for (name, value) in mirrored_headers(&req) {
+            builder.append_header((name, value.clone()));
+        }
+    }
+
+    if let Some(location) = redirect {
+        builder.insert_header((LOCATION, location));
+    }
+
+    if let Some(challenge) = challenge {
+        builder.insert_header((WWW_AUTHENTICATE, challenge));
+    }
+
+    if req.method() == Method::OPTIONS {
+        builder.insert_header((ALLOW, ALLOWED_METHODS));
+    }
+
+    if let Some(link) = resp
+        .query_param(PAGINATE_PARAM)
+        .and_then(|spec| pagination::link_header(&resp.path, spec))
+    {
+        builder.insert_header((LINK, link));
+    }
+
+    if let Some(window) = &state.batch_window {
+        let echo = serde_json::to_value(&resp).map_err(ErrorInternalServerError)?;
+        return Ok(match window.add(echo, Instant::now()) {
+            Some(batch) => builder.json(batch),
+            None => builder.status(StatusCode::ACCEPTED).json(&resp),
+        });
+    }
+
+    if resp
+        .query_param(INFINITE_PARAM)
+        .is_some_and(infinite::enabled)
+    {
+        return Ok(builder
+            .content_type(NDJSON_CONTENT_TYPE)
+            .streaming(infinite::stream(resp.request_id)));
+    }
+
+    if resp.query_param(SPLIT_PARAM) == Some(SPLIT_CHUNKS) {
+        let value = serde_json::to_value(&resp).map_err(ErrorInternalServerError)?;
+        let delay = Duration::from_millis(state.settings.split_delay_ms);
+        return Ok(builder
+            .content_type(ContentType::json())
+            .streaming(split::stream(split::chunks(value), delay)));
+    }
+
+    let transformed = match (&state.jq_transform, &resp.body.json) {
+        (Some(transform), Some(json)) => match transform.apply(json) {
+            Ok(value) => Some(value),
+            Err(error) => return Ok(HttpResponse::BadRequest().json(ErrorResponse { error })),
+        },
+        _ => None,
+    };
+
+    let echo = resp.query_param(ECHO_PARAM);
+
+    let (content_type, mut body) = match (&state.protobuf, transformed) {
+        (_, Some(value)) => (
+            JSON_CONTENT_TYPE,
+            serde_json::to_vec(&value).map_err(ErrorInternalServerError)?,
+        ),
+        (Some(encoder), None) if format == PROTOBUF_CONTENT_TYPE => (
+            PROTOBUF_CONTENT_TYPE,
+            encoder.encode(&resp).map_err(ErrorInternalServerError)?,
+        ),
+        _ if echo == Some(ECHO_EVENT) => (
+            CLOUDEVENTS_CONTENT_TYPE,
+            serde_json::to_vec(&envelope::cloud_event(&resp, chrono::Utc::now()))
+                .map_err(ErrorInternalServerError)?,
+        ),
+        _ if echo == Some(ECHO_LOG_EVENT) => (
+            JSON_CONTENT_TYPE,
+            serde_json::to_vec(&envelope::log_event(&resp, chrono::Utc::now()))
+                .map_err(ErrorInternalServerError)?,
+        ),
+        _ if echo == Some(ECHO_NGINX) => {
+            let request_line = format!(
+                "{} {} {:?}",
+                resp.method,
+                req.uri().path_and_query().map_or(path, |p| p.as_str()),
+                req.version()
+            );
+            let line = envelope::nginx_access_log(
+                &resp,
+                &request_line,
+                status.as_u16(),
+                bytes.len(),
+                chrono::Utc::now(),
+            );
+            (TEXT_PLAIN_CONTENT_TYPE, line.into_bytes())
+        }
+        _ if envelope::raw_echo_requested(echo, req.headers()) => (
+            req.headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or(DOWNLOAD_CONTENT_TYPE),
+            bytes.to_vec(),
+        ),
+        _ if echo == Some(ECHO_APIGW) => (
+            JSON_CONTENT_TYPE,
+            serde_json::to_vec(&envelope::api_gateway_event(
+                &resp,
+                &bytes,
+                chrono::Utc::now(),
+            ))
+            .map_err(ErrorInternalServerError)?,
+        ),
+        _ if echo == Some(ECHO_DOWNLOAD) => {
+            builder.insert_header((CONTENT_DISPOSITION, DOWNLOAD_CONTENT_DISPOSITION));
+            (DOWNLOAD_CONTENT_TYPE, bytes.to_vec())
+        }
+        _ if echo == Some(ECHO_AMQP) => (
+            JSON_CONTENT_TYPE,
+            serde_json::to_vec(&envelope::amqp_message(&resp, chrono::Utc::now()))
+                .map_err(ErrorInternalServerError)?,
+        ),
+        _ if format == HTML_CONTENT_TYPE => {
+            let value = serde_json::to_value(&resp).map_err(ErrorInternalServerError)?;
+            (HTML_CONTENT_TYPE, html::highlight(&value).into_bytes())
+        }
+        _ => {
+            let stable = state.settings.stable_snapshots
+                || resp.query_param(STABLE_PARAM).is_some_and(stable::enabled);
+            let filter = FieldFilter::new(
+                resp.query_param(FIELDS_PARAM),
+                resp.query_param(EXCLUDE_PARAM),
+            );
+            serialize_echo(
+                &resp,
+                &format,
+                stable,
+                &filter,
+                &state.settings.field_renames,
+            )?
+        }
+    };
+
+    if content_type == JSON_CONTENT_TYPE && resp.query_param(BAD_PARAM) == Some(BAD_JSON) {
+        malformed::break_json(&mut body);
+    }
+
+    if let Some(secret) = &state.settings.response_signing_secret {
+        let algorithm = state.settings.response_signing_algorithm;
+        builder.insert_header((SIGNATURE_HEADER, signing::sign(algorithm, secret, &body)));
+    }
+
+    if let Some(signer) = &state.ed25519_signer {
+        builder.insert_header((ED25519_SIGNATURE_HEADER, signer.sign(&body)));
+        builder.insert_header((PUBLIC_KEY_HEADER, signer.public_key()));
+    }
+
+    Ok(builder.content_type(content_type).body(body))
+}
+
+/// Serializes the echo as XML or MessagePack when it's the negotiated format, as JSON otherwise.
+///
+/// A `stable` echo has its volatile fields replaced with placeholders. Fields are filtered before
+/// being renamed, by their original name.
+fn serialize_echo(
+    resp: &CatchallResponse,
+    format: &str,
+    stable: bool,
+    filter: &FieldFilter,
+    renames: &HashMap<String, String>,
+) -> Result<(&'static str, Vec<u8>)> {
+    if format == JSON_CONTENT_TYPE && !stable && filter.is_empty() && renames.is_empty() {
+        let body = serde_json::to_vec(resp).map_err(ErrorInternalServerError)?;
+        return Ok((JSON_CONTENT_TYPE, body));
+    }
+
+    let mut value = serde_json::to_value(resp).map_err(ErrorInternalServerError)?;
+    if stable {
+        value = stable::snapshot(value);
+    }
+    let value = rename::rename_fields(filter.apply(value), renames);
+
+    Ok(match format {
+        XML_CONTENT_TYPE => (XML_CONTENT_TYPE, xml::to_xml(&value)),
+        MSGPACK_CONTENT_TYPE => (
+            MSGPACK_CONTENT_TYPE,
+            msgpack::encode(&value).map_err(ErrorInternalServerError)?,
+        ),
+        _ => (
+            JSON_CONTENT_TYPE,
+            serde_json::to_vec(&value).map_err(ErrorInternalServerError)?,
+        ),
+    })
+}
+
+/// Request headers prefixed with `X-Echo-`, under their name without the prefix. Those whose name
+/// is only the prefix are skipped.
+fn mirrored_headers(request: &HttpRequest) -> impl Iterator<Item = (HeaderName, &HeaderValue)> {
+    request.headers().iter().filter_map(|(name, value)| {
+        let name = name.as_str().strip_prefix(MIRROR_HEADER_PREFIX)?;
+        Some((HeaderName::try_from(name).ok()?, value))
+    })
+}
+
+fn is_json(request: &HttpRequest) -> bool {
+    is_json_content_type(request.content_type())
+}
+
+/// `application/json` or a `+json` structured syntax suffix, e.g. `application/problem+json`.
+fn is_json_content_type(content_type: &str) -> bool {
+    content_type == JSON_CONTENT_TYPE || content_type.ends_with("+json")
+}
+
+fn get_accept(request: &HttpRequest) -> Vec<MediaRange> {
+    let header = request
+        .headers()
+        .get_all(ACCEPT)
+        .filter_map(|v| v.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    accept::parse(&header)
+}
+
+/// Picks the format of the echo from the `Accept` header: Protocol Buffers or highlighted HTML
+/// when enabled and accepted, XML or MessagePack when preferred over JSON, JSON otherwise.
+fn negotiate(request: &HttpRequest, state: &AppState) -> NegotiationInfo {
+    let accept = request
+        .headers()
+        .get_all(ACCEPT)
+        .filter_map(|v| v.to_str().ok())
+        .collect::<Vec<_>>();
+    let accept = (!accept.is_empty()).then(|| accept.join(","));
+
+    let mut supported = Vec::new();
+    if state.protobuf.is_some() {
+        supported.push(PROTOBUF_CONTENT_TYPE);
+    }
+    if state.settings.html_highlight {
+        supported.push(HTML_CONTENT_TYPE);
+    }
+    supported.extend(SERIALIZED_CONTENT_TYPES);
+
+    let (chosen, reason) =
+        if supported.contains(&PROTOBUF_CONTENT_TYPE) && accepts(request, PROTOBUF_CONTENT_TYPE) {
+            (PROTOBUF_CONTENT_TYPE, "explicitly accepted".to_string())
+        } else if supported.contains(&HTML_CONTENT_TYPE) && accepts(request, HTML_CONTENT_TYPE) {
+            (HTML_CONTENT_TYPE, "explicitly accepted".to_string())
+        } else {
+            match accept::preferred(&get_accept(request), &SERIALIZED_CONTENT_TYPES) {
+                Some(chosen) => {
+                    let others: Vec<_> = SERIALIZED_CONTENT_TYPES
+                        .into_iter()
+                        .filter(|t| *t != chosen)
+                        .collect();
+                    (chosen, format!("preferred over {}", others.join(", ")))
+                }
+                None if accept.is_none() => {
+                    (JSON_CONTENT_TYPE, "default, no Accept header".to_string())
+                }
+                None => (
+                    JSON_CONTENT_TYPE,
+                    "default, no supported media type explicitly accepted".to_string(),
+                ),
+            }
+        };
+
+    NegotiationInfo {
+        accept,
+        supported: supported.iter().map(|t| t.to_string()).collect(),
+        chosen: chosen.to_string(),
+        reason,
+    }
+}
+
+/// Whether the client explicitly accepts `media_type`, wildcards aren't considered.
+fn accepts(request: &HttpRequest, media_type: &str) -> bool {
+    get_accept(request)
+        .iter()
+        .any(|r| r.q > 0.0 && r.essence().eq_ignore_ascii_case(media_type))
+}
+
+fn get_conditional(request: &HttpRequest) -> ConditionalInfo {
+    let header = |name: HeaderName| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+
+    ConditionalInfo {
+        if_match: header(IF_MATCH),
+        if_none_match: header(IF_NONE_MATCH),
+        if_modified_since: header(IF_MODIFIED_SINCE),
+        if_unmodified_since: header(IF_UNMODIFIED_SINCE),
+    }
+}
+
+fn get_client(request: &HttpRequest) -> ClientInfo {
+    let conn_info = request.connection_info();
+    let remote_ip = conn_info.realip_remote_addr().map(|s| s.to_string());
+
+    let port = request.peer_addr().map(|addr| addr.port());
+
+    ClientInfo {
+        remote_ip,
+        port,
+        geo: None,
+    }
 }
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
-struct Body {
-    json: Option<Value>,
-    raw: String,
-}
+/// Parses an `application/x-www-form-urlencoded` query string, keeping every value of repeated
+/// parameters. Parameters without a value, e.g. `?flag`, get an empty one.
+fn get_query_params(query_string: &str) -> HashMap<String, Vec<String>> {
+    let decode = |s: &str| {
+        percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .to_string()
+    };
+
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    for pair in query_string.split('&').filter(|p| !p.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params.entry(decode(name)).or_default().push(decode(value));
+    }
+
+    params
+}
+
+/// actix takes the scheme and host from the `Forwarded` and `X-Forwarded-*` headers when present,
+/// from the connection otherwise.
+fn get_url_info(request: &HttpRequest) -> UrlInfo {
+    let conn_info = request.connection_info();
+
+    let host = conn_info.host();
+    let hostname = host.split(':').next().unwrap_or("").to_string();
+    let port = host
+        .split(':')
+        .nth(1)
+        .map(|p| p.parse::<u16>().unwrap_or(0))
+        .unwrap_or(0);
+
+    let mut full_uri = format!("{}://{}{}", conn_info.scheme(), host, request.path());
+    if !request.query_string().is_empty() {
+        full_uri.push('?');
+        full_uri.push_str(request.query_string());
+    }
+
+    UrlInfo {
+        scheme: conn_info.scheme().to_string(),
+        hostname,
+        port,
+        path: request.path().to_string(),
+        path_segments: get_path_segments(request.path()),
+        full_uri,
+        forwarded_proto: forwarded::proto(request.headers()),
+        forwarded_host: forwarded::host(request.headers()),
+    }
+}
+
+fn get_path_segments(path: &str) -> Vec<String> {
+    let path = path.strip_prefix('/').unwrap_or(path);
+    if path.is_empty() {
+        return Vec::new();
+    }
+
+    path.split('/')
+        .map(|s| percent_decode_str(s).decode_utf8_lossy().to_string())
+        .collect()
+}
+
+fn get_headers(request: &HttpRequest) -> HashMap<String, Vec<String>> {
+    let mut headers: HashMap<String, Vec<String>> = HashMap::new();
+    for (name, value) in request.headers() {
+        headers
+            .entry(name.to_string())
+            .or_default()
+            .push(value.to_str().unwrap_or("").to_string());
+    }
+
+    headers
+}
+
+/// Cookies of every `Cookie` header, none if any of them can't be parsed.
+fn get_cookies(request: &HttpRequest) -> HashMap<String, String> {
+    request
+        .cookies()
+        .map(|cookies| {
+            cookies
+                .iter()
+                .map(|c| (c.name().to_string(), c.value().to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Only bodies declared as JSON or MessagePack are parsed into `json`, every body is
+/// base64-encoded into `raw`.
+fn get_body(bytes: web::Bytes, content_type: &str, settings: &AppSettings) -> Body {
+    let total_size = bytes.len();
+    let truncated = total_size > settings.max_body_bytes;
+    let bytes = bytes.slice(..total_size.min(settings.max_body_bytes));
+
+    let json: Option<Value> = match content_type {
+        _ if truncated => None,
+        t if is_json_content_type(t) => serde_json::from_slice(&bytes).ok(),
+        t if msgpack::is_msgpack(t) => msgpack::decode(&bytes),
+        _ => None,
+    };
+    let text = std::str::from_utf8(&bytes).ok().map(str::to_string);
+    let (raw, raw_encoding) = if settings.compress_raw_body {
+        (
+            b64engine.encode(gzip(&bytes)),
+            Some(GZIP_BASE64_ENCODING.to_string()),
+        )
+    } else {
+        (b64engine.encode(bytes), None)
+    };
+
+    Body {
+        json_depth: json.as_ref().map(json_shape::depth),
+        json_node_count: json.as_ref().map(json_shape::node_count),
+        json,
+        raw,
+        text,
+        raw_encoding,
+        multipart: None,
+        toml: None,
+        ini: None,
+        form: None,
+        decoded_with: None,
+        truncated,
+        total_size,
+    }
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(bytes)
+        .expect("writing to a Vec can't fail");
+    encoder.finish().expect("writing to a Vec can't fail")
+}
+
+/// Parses `multipart/*` bodies. Since the body is fully buffered before reaching the handler, the
+/// response is only sent once every part has been received.
+fn get_multipart(request: &HttpRequest, bytes: &[u8]) -> Option<MultipartInfo> {
+    let mime = request.mime_type().ok()??;
+    if mime.type_() != mime::MULTIPART {
+        return None;
+    }
+
+    let boundary = mime.get_param(mime::BOUNDARY)?;
+    multipart::parse(bytes, boundary.as_str())
+}
+
+/// actix decodes compressed payloads before they reach the handler, so a body with a supported
+/// `Content-Encoding` has always been decoded by then.
+fn get_decoded_with(request: &HttpRequest) -> Option<String> {
+    let encoding = request
+        .headers()
+        .get(CONTENT_ENCODING)?
+        .to_str()
+        .ok()?
+        .parse::<ContentEncoding>()
+        .ok()?;
+
+    match encoding {
+        ContentEncoding::Identity => None,
+        encoding => Some(encoding.as_str().to_string()),
+    }
+}
+
+fn get_toml(request: &HttpRequest, bytes: &[u8]) -> Option<Value> {
+    if !TOML_CONTENT_TYPES.contains(&request.content_type()) {
+        return None;
+    }
+
+    toml::from_str(std::str::from_utf8(bytes).ok()?).ok()
+}
+
+fn get_form(request: &HttpRequest, bytes: &[u8]) -> Option<HashMap<String, Vec<String>>> {
+    if request.content_type() != FORM_CONTENT_TYPE {
+        return None;
+    }
+
+    std::str::from_utf8(bytes).ok().map(get_query_params)
+}
+
+/// Parses `text/ini` bodies, as well as untyped or `text/plain` ones starting with a section header.
+fn get_ini(
+    request: &HttpRequest,
+    bytes: &[u8],
+) -> Option<HashMap<String, HashMap<String, String>>> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let declared = request.content_type() == INI_CONTENT_TYPE;
+    let untyped = matches!(request.content_type(), "" | "text/plain");
+    if !(declared || untyped && starts_with_ini_section(text)) {
+        return None;
+    }
+
+    let ini = ini::Ini::load_from_str(text).ok()?;
+
+    Some(
+        ini.iter()
+            .filter(|(section, props)| section.is_some() || !props.is_empty())
+            .map(|(section, props)| {
+                let props = props
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                (section.unwrap_or("").to_string(), props)
+            })
+            .collect(),
+    )
+}
+
+/// Whether the first line that isn't blank or a comment is a `[section]` header.
+fn starts_with_ini_section(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with(';') && !l.starts_with('#'))
+        .and_then(|l| l.strip_prefix('[')?.strip_suffix(']'))
+        .is_some_and(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || " ._-".contains(c))
+        })
+}
+
+fn configure_app(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(HELP_PATH).route(web::get().to(help::help)));
+    cfg.service(web::resource(STATS_PATH).route(web::get().to(stats::path_stats)));
+    cfg.service(web::resource(HISTORY_PATH).route(web::get().to(history::history)));
+    cfg.service(web::resource(REQUESTS_PATH).route(web::get().to(history::requests)));
+    cfg.service(web::resource(METRICS_PATH).route(web::get().to(metrics::metrics)));
+    cfg.service(web::resource(OAUTH_TOKEN_PATH).route(web::post().to(oauth::token)));
+    cfg.service(
+        web::resource("{path:.*}")
+            .route(web::delete().to(handler))
+            .route(web::get().to(handler))
+            .route(web::head().to(handler))
+            .route(web::method(Method::OPTIONS).to(handler))
+            .route(web::patch().to(handler))
+            .route(web::post().to(handler))
+            .route(web::put().to(handler)),
+    );
+}
+
+fn create_app(
+    state: web::Data<AppState>,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = Error,
+        InitError = (),
+    >,
+> {
+    let payload_limit = state.content_type_limits.max();
+    let health_path = state.settings.health_path.clone();
+    // An empty env var gives a single empty origin.
+    let origins: Vec<_> = state
+        .settings
+        .cors_allowed_origins
+        .iter()
+        .filter(|origin| !origin.is_empty())
+        .map(String::as_str)
+        .collect();
+    let cors = Condition::new(!origins.is_empty(), cors(&origins));
+
+    App::new()
+        .app_data(state)
+        .app_data(web::PayloadConfig::new(payload_limit))
+        .service(
+            web::resource(health_path)
+                .route(web::get().to(health::health))
+                .route(web::head().to(health::health)),
+        )
+        .configure(configure_app)
+        .wrap(from_fn(slow_read::slow_read))
+        .wrap(from_fn(body_limit::limit_body))
+        .wrap(from_fn(connection_limit::limit_connections))
+        .wrap(from_fn(stats::record_stats))
+        .wrap(from_fn(fuzz::inject_headers))
+        .wrap(from_fn(retry_after::add_retry_after))
+        .wrap(cors)
+}
+
+/// Allows any method and header from the allowed origins, any origin if `*` is one of them.
+fn cors(origins: &[&str]) -> Cors {
+    let cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        .expose_any_header();
+
+    if origins.contains(&"*") {
+        return cors.allow_any_origin();
+    }
+    origins
+        .iter()
+        .fold(cors, |cors, origin| cors.allowed_origin(origin))
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let settings: AppSettings = get_config()
+        .expect("valid config")
+        .try_deserialize()
+        .expect("valid config");
+
+    logging::init(settings.log_format, settings.request_log_format);
+
+    let tls_config = tls::server_config(
+        settings.tls_cert_path.as_deref(),
+        settings.tls_key_path.as_deref(),
+    )
+    .expect("valid config");
+    let state = web::Data::new(AppState::new(settings).expect("valid config"));
+    let host = state.settings.host.clone();
+    let port = state.settings.port;
+    let socket_path = state.settings.unix_socket.clone();
+    let workers = state.settings.workers;
+    let shutdown_timeout_secs = state.settings.shutdown_timeout_secs;
+    let health_path = state.settings.health_path.clone();
+
+    let server = HttpServer::new(move || {
+        create_app(state.clone()).wrap(Logger::default().exclude(health_path.clone()))
+    })
+    .workers(workers)
+    .shutdown_timeout(shutdown_timeout_secs)
+    .disable_signals();
+    let server = match (&socket_path, tls_config) {
+        (Some(_), Some(_)) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "TLS isn't supported on a unix socket",
+            ))
+        }
+        (Some(path), None) => {
+            info!("Starting server on {}", path);
+            unix_socket::remove_stale(Path::new(path))?;
+            server.bind_uds(path)?
+        }
+        (None, Some(config)) => {
+            info!("Starting server on {}:{}", host, port);
+            server.bind_rustls_0_23((host, port), config)?
+        }
+        (None, None) => {
+            info!("Starting server on {}:{}", host, port);
+            server.bind((host, port))?
+        }
+    }
+    .run();
+
+    let handle = server.handle();
+    rt::spawn(async move {
+        shutdown_signal().await;
+        info!(
+            "Shutting down, waiting up to {}s for in-flight requests",
+            shutdown_timeout_secs
+        );
+        handle.stop(true).await;
+    });
+
+    server.await?;
+    if let Some(path) = socket_path {
+        unix_socket::remove_stale(Path::new(&path))?;
+    }
+    info!("Shutdown complete");
+
+    Ok(())
+}
+
+/// Resolves on the first `SIGINT` or `SIGTERM`.
+async fn shutdown_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+
+    future::select(pin!(rt::signal::ctrl_c()), pin!(sigterm.recv())).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_http::Request;
+    use actix_web::{
+        dev::Service,
+        http::{
+            header::{
+                ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+                ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_HEADERS,
+                ACCESS_CONTROL_REQUEST_METHOD, CONTENT_TYPE, ORIGIN, RETRY_AFTER, X_FORWARDED_FOR,
+            },
+            Version,
+        },
+        test,
+    };
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use flate2::write::ZlibEncoder;
+    use hmac::{KeyInit, Mac};
+    use rstest::rstest;
+    use std::io::Read;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::time::Instant;
+
+    async fn get_test_app(
+    ) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error>
+    {
+        get_test_app_with_settings(AppSettings::default()).await
+    }
+
+    async fn get_test_app_with_settings(
+        settings: AppSettings,
+    ) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error>
+    {
+        get_test_app_with_state(AppState::new(settings).expect("valid settings")).await
+    }
+
+    async fn get_test_app_with_state(
+        state: AppState,
+    ) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error>
+    {
+        test::init_service(create_app(web::Data::new(state))).await
+    }
+
+    /// Starts a server on a random port, for the tests needing an actual connection.
+    fn start_test_server(state: AppState) -> (SocketAddr, actix_web::dev::ServerHandle) {
+        let state = web::Data::new(state);
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = HttpServer::new(move || create_app(state.clone()))
+            .workers(1)
+            .listen(listener)
+            .unwrap()
+            .run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        (addr, handle)
+    }
+
+    /// Sends a raw HTTP request and reads the response until `limit` bytes are received or the
+    /// connection is closed, then disconnects.
+    async fn raw_request(addr: SocketAddr, request: &'static str, limit: usize) -> String {
+        actix_web::rt::task::spawn_blocking(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut received = Vec::new();
+            let mut buf = [0; 8192];
+            while received.len() < limit {
+                match stream.read(&mut buf).unwrap() {
+                    0 => break,
+                    n => received.extend_from_slice(&buf[..n]),
+                }
+            }
+            String::from_utf8_lossy(&received).to_string()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_handler_empty_request() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .peer_addr("192.168.42.69:12345".parse().unwrap())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        let expected = CatchallResponse {
+            request_id: body.request_id.clone(),
+            received_at: body.received_at.clone(),
+            method: "GET".to_string(),
+            http_version: "HTTP/1.1".to_string(),
+            path: "/".to_string(),
+            client: ClientInfo {
+                remote_ip: Some("192.168.42.69".to_string()),
+                port: Some(12345),
+                geo: None,
+            },
+            url: UrlInfo {
+                scheme: "http".to_string(),
+                hostname: "localhost".to_string(),
+                port: 8080,
+                path: "/".to_string(),
+                path_segments: vec![],
+                full_uri: "http://localhost:8080/".to_string(),
+                forwarded_proto: None,
+                forwarded_host: None,
+            },
+            body: Body {
+                text: Some(String::new()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(body, expected);
+    }
+
+    #[rstest]
+    #[case("/foo/bar?x=1", "http://localhost:8080/foo/bar?x=1")]
+    #[case("/foo/bar", "http://localhost:8080/foo/bar")]
+    #[case("/foo/bar?", "http://localhost:8080/foo/bar")]
+    #[case("/a%20b?q=%2F", "http://localhost:8080/a%20b?q=%2F")]
+    #[actix_web::test]
+    async fn test_handler_returns_full_uri(#[case] uri: &str, #[case] expected: &str) {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get().uri(uri).send_request(&app).await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.url.full_uri, expected);
+    }
+
+    #[rstest]
+    #[case(&[("X-Forwarded-Proto", "https"), ("X-Forwarded-Host", "api.example:8443")])]
+    #[case(&[("Forwarded", "for=192.0.2.60;proto=https;host=api.example:8443")])]
+    #[actix_web::test]
+    async fn test_handler_returns_forwarded_url(#[case] headers: &[(&str, &str)]) {
+        let app = get_test_app().await;
+
+        let mut req = test::TestRequest::get().uri("/foo?x=1");
+        for header in headers {
+            req = req.insert_header(*header);
+        }
+        let resp = req.send_request(&app).await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.url,
+            UrlInfo {
+                scheme: "https".to_string(),
+                hostname: "api.example".to_string(),
+                port: 8443,
+                path: "/foo".to_string(),
+                path_segments: vec!["foo".to_string()],
+                full_uri: "https://api.example:8443/foo?x=1".to_string(),
+                forwarded_proto: Some("https".to_string()),
+                forwarded_host: Some("api.example:8443".to_string()),
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_unknown_client_port_without_peer() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.client.port, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_received_at() {
+        let app = get_test_app().await;
+
+        let before = chrono::Utc::now();
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+        let after = chrono::Utc::now();
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        let received_at = chrono::DateTime::parse_from_rfc3339(&body.received_at).unwrap();
+
+        assert!(body.received_at.ends_with('Z'));
+        assert!(received_at >= before - chrono::Duration::milliseconds(1));
+        assert!(received_at <= after);
+    }
+
+    #[rstest]
+    #[case(-60, true)]
+    #[case(3600, false)]
+    #[actix_web::test]
+    async fn test_handler_returns_jwt_expiry(#[case] expires_in: i64, #[case] expired: bool) {
+        let app = get_test_app_with_settings(AppSettings {
+            decode_jwt: true,
+            ..Default::default()
+        })
+        .await;
+
+        let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        let exp = chrono::Utc::now().timestamp() + expires_in;
+        let token = format!(
+            "{}.{}.c2ln",
+            b64.encode(r#"{"alg":"HS256"}"#),
+            b64.encode(format!(r#"{{"sub":"me","exp":{}}}"#, exp))
+        );
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        let jwt = body.jwt.unwrap();
+
+        assert_eq!(jwt.claims["sub"], "me");
+        assert_eq!(jwt.expired, Some(expired));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_conditional_headers() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((IF_NONE_MATCH, "\"v1\""))
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.conditional,
+            ConditionalInfo {
+                if_none_match: Some("\"v1\"".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(Version::HTTP_10, "HTTP/1.0")]
+    #[case(Version::HTTP_11, "HTTP/1.1")]
+    #[case(Version::HTTP_2, "HTTP/2.0")]
+    #[actix_web::test]
+    async fn test_handler_returns_http_version(#[case] version: Version, #[case] expected: &str) {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .version(version)
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.http_version, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_request_id() {
+        let app = get_test_app_with_settings(AppSettings {
+            request_id_format: request_id::RequestIdFormat::Ulid,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+        let first: CatchallResponse = test::read_body_json(resp).await;
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+        let second: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(first.request_id.len(), 26);
+        assert_ne!(first.request_id, second.request_id);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_request_id_header() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+        let header = resp.headers().get(X_REQUEST_ID).unwrap().clone();
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(header, body.request_id.as_str());
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((X_REQUEST_ID, "trace-123"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.headers().get(X_REQUEST_ID).unwrap(), "trace-123");
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.request_id, "trace-123");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_request_id_header_on_batch_responses() {
+        let app = get_test_app_with_settings(AppSettings {
+            batching: Some(batch::Batching {
+                window_ms: 60000,
+                max_requests: 2,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get().uri("/a").send_request(&app).await;
+
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let header = resp.headers().get(X_REQUEST_ID).unwrap().clone();
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(header, body.request_id.as_str());
+
+        let resp = test::TestRequest::get()
+            .uri("/b")
+            .insert_header((X_REQUEST_ID, "trace-123"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(X_REQUEST_ID).unwrap(), "trace-123");
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    #[actix_web::test]
+    async fn test_handler_with_trace_body_attributes(#[case] enabled: bool) {
+        let app = get_test_app_with_settings(AppSettings {
+            request_log_format: otel::RequestLogFormat::Otel,
+            trace_body_attributes: enabled,
+            trace_body_preview_bytes: 4,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload("foobar")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_applies_content_type_limits() {
+        let app = get_test_app_with_settings(AppSettings {
+            content_type_limits: HashMap::from([
+                (JSON_CONTENT_TYPE.to_string(), 16),
+                ("multipart/*".to_string(), 512 * 1024),
+            ]),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"foo": "barbazqux"}"#)
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Over the default limit, but under the one of multipart bodies.
+        let file = "a".repeat(300 * 1024);
+        let payload = format!(
+            "--XyZ\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+            \r\n\
+            {}\r\n\
+            --XyZ--\r\n",
+            file
+        );
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, "multipart/form-data; boundary=XyZ"))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.multipart.unwrap().total_bytes, 300 * 1024);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_reports_unsupported_trailers() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?__trailer=X-Checksum:abc")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+
+        let body: ErrorResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.error,
+            "cannot send the x-checksum trailer: actix-web doesn't support HTTP trailers"
+        );
+
+        let resp = test::TestRequest::get()
+            .uri("/?__trailer=X-Checksum")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_rejects_missing_query_params() {
+        let app = get_test_app_with_settings(AppSettings {
+            required_query_params: vec!["tenant".to_string(), "version".to_string()],
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?version=2")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let body: MissingQueryParamsResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.missing, vec!["tenant"]);
+
+        let resp = test::TestRequest::get()
+            .uri("/?version=2&tenant=acme")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_requires_captive_portal_login() {
+        let app = get_test_app_with_settings(AppSettings {
+            captive_portal: Some(captive_portal::CaptivePortal {
+                session_cookie: "session".to_string(),
+                login_url: "https://portal.example/login".to_string(),
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/orders")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::NETWORK_AUTHENTICATION_REQUIRED);
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), HTML_CONTENT_TYPE);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains("<a href=\"https://portal.example/login\">"));
+
+        let resp = test::TestRequest::get()
+            .uri("/orders")
+            .insert_header(("Cookie", "session=abc"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_delays_proportionally_to_body_size() {
+        let app = get_test_app_with_settings(AppSettings {
+            delay_per_kb_ms: Some(20),
+            ..Default::default()
+        })
+        .await;
+
+        let mut elapsed = Vec::new();
+        for size in [1024, 5 * 1024] {
+            let started = Instant::now();
+            let resp = test::TestRequest::post()
+                .uri("/")
+                .set_payload(vec![b'a'; size])
+                .send_request(&app)
+                .await;
+            elapsed.push(started.elapsed());
+
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        assert!(elapsed[0] >= Duration::from_millis(20));
+        assert!(elapsed[1] >= Duration::from_millis(100));
+        assert!(elapsed[1] > elapsed[0]);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_requires_payment_of_paid_paths() {
+        let app = get_test_app_with_settings(AppSettings {
+            paywall: Some(paywall::Paywall {
+                paths: vec!["/premium".to_string()],
+                token: "paid".to_string(),
+                amount: "4.99".to_string(),
+                currency: "EUR".to_string(),
+                payment_url: "https://pay.example/checkout".to_string(),
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/premium")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+
+        let body: paywall::PaymentRequired = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body,
+            paywall::PaymentRequired {
+                error: "payment required".to_string(),
+                amount: "4.99".to_string(),
+                currency: "EUR".to_string(),
+                payment_url: "https://pay.example/checkout".to_string(),
+            }
+        );
+
+        let resp = test::TestRequest::get()
+            .uri("/premium")
+            .insert_header(("X-Payment-Token", "paid"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.path, "/premium");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_mirrors_echo_headers() {
+        let app = get_test_app_with_settings(AppSettings {
+            mirror_echo_headers: true,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("X-Echo-Trace-Id", "abc"))
+            .insert_header(("X-Echo-", "ignored"))
+            .insert_header(("X-Other", "def"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.headers().get("trace-id").unwrap(), "abc");
+        assert!(resp.headers().get("x-other").is_none());
+        assert!(resp.headers().get("other").is_none());
+
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("X-Echo-Trace-Id", "abc"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.headers().get("trace-id").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_cors_allows_any_origin_by_default() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((ORIGIN, "https://app.example"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://app.example"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_cors_answers_preflight_requests() {
+        let app = get_test_app_with_settings(AppSettings {
+            cors_allowed_origins: vec!["https://app.example".to_string()],
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/orders")
+            .insert_header((ORIGIN, "https://app.example"))
+            .insert_header((ACCESS_CONTROL_REQUEST_METHOD, "PUT"))
+            .insert_header((ACCESS_CONTROL_REQUEST_HEADERS, "x-tenant"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://app.example"
+        );
+        assert!(resp
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_METHODS)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("PUT"));
+        assert_eq!(
+            resp.headers().get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap(),
+            "x-tenant"
+        );
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((ORIGIN, "https://other.example"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_cors_is_disabled_without_allowed_origins() {
+        let app = get_test_app_with_settings(AppSettings {
+            cors_allowed_origins: Vec::new(),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((ORIGIN, "https://app.example"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_requires_upgrade_of_configured_paths() {
+        let app = get_test_app_with_settings(AppSettings {
+            upgrade_required_paths: vec![path_value::PathValue::new("/tls", "TLS/1.3".to_string())],
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/tls")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(resp.headers().get(UPGRADE).unwrap(), "TLS/1.3");
+        assert_eq!(resp.headers().get(CONNECTION).unwrap(), "Upgrade");
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_rejects_blocked_paths() {
+        let app = get_test_app_with_settings(AppSettings {
+            blocked_paths: vec![path_value::PathValue::new(
+                "/banned",
+                "https://authority.example/notices/42".to_string(),
+            )],
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/banned")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS);
+        assert_eq!(
+            resp.headers().get(LINK).unwrap(),
+            "<https://authority.example/notices/42>; rel=\"blocked-by\""
+        );
+
+        let resp = test::TestRequest::get()
+            .uri("/banned/not")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_degrades_periodically() {
+        let app = get_test_app_with_settings(AppSettings {
+            health_window: Some(degradation::HealthWindow {
+                window_size: 3,
+                healthy_in_window: 2,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let mut statuses = Vec::new();
+        for _ in 0..6 {
+            let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+            statuses.push(resp.status());
+        }
+
+        let window = [
+            StatusCode::OK,
+            StatusCode::OK,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ];
+        assert_eq!(statuses, [window, window].concat());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_cached_echo_for_identical_requests() {
+        let app = get_test_app_with_settings(AppSettings {
+            dedup_cache: Some(dedup::DedupCache {
+                ttl_ms: 60_000,
+                max_entries: 10,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let send = |payload: &'static str| {
+            test::TestRequest::post()
+                .uri("/orders")
+                .set_payload(payload)
+                .send_request(&app)
+        };
+
+        let first: CatchallResponse = test::read_body_json(send("{\"id\": 1}").await).await;
+        let second: CatchallResponse = test::read_body_json(send("{\"id\": 1}").await).await;
+        let other: CatchallResponse = test::read_body_json(send("{\"id\": 2}").await).await;
+
+        assert_eq!(first.cache_hit, Some(false));
+        assert_eq!(second.cache_hit, Some(true));
+        assert_eq!(second.request_id, first.request_id);
+        assert_eq!(other.cache_hit, Some(false));
+        assert_ne!(other.request_id, first.request_id);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_idempotency_fingerprint() {
+        let app = get_test_app_with_settings(AppSettings {
+            idempotency_tracking: Some(idempotency::IdempotencyTracking { max_entries: 10 }),
+            ..Default::default()
+        })
+        .await;
+
+        let send = |payload: &'static str| {
+            test::TestRequest::post()
+                .uri("/orders")
+                .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+                .set_payload(payload)
+                .send_request(&app)
+        };
+
+        let first: CatchallResponse = test::read_body_json(send(r#"{"id": 1}"#).await).await;
+        sleep(Duration::from_millis(10)).await;
+        let second: CatchallResponse = test::read_body_json(send(r#"{ "id":1 }"#).await).await;
+
+        let first = first.idempotency.unwrap();
+        let second = second.idempotency.unwrap();
+        assert_eq!(first.age_secs, 0.0);
+        assert!(second.age_secs > 0.0);
+        assert_eq!(second.fingerprint, first.fingerprint);
+        assert_eq!(second.first_seen, first.first_seen);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_rolling_server_version() {
+        let app = get_test_app_with_settings(AppSettings {
+            rolling_versions: Some(rollout::RollingVersions {
+                versions: vec!["1.0.0".to_string(), "1.1.0".to_string()],
+                requests_per_version: 3,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let mut versions = Vec::new();
+        for _ in 0..7 {
+            let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+            let body: CatchallResponse = test::read_body_json(resp).await;
+            versions.push(body.server.unwrap().version);
+        }
+
+        assert_eq!(
+            versions,
+            vec!["1.0.0", "1.0.0", "1.0.0", "1.1.0", "1.1.0", "1.1.0", "1.0.0"]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_path() {
+        let app = get_test_app().await;
+        let resp = test::TestRequest::get()
+            .uri("/foo/bar?baz=1")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.path, "/foo/bar".to_string());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_decoded_path_segments() {
+        let app = get_test_app().await;
+        let resp = test::TestRequest::get()
+            .uri("/foo/a%2Fb/hello%20world")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.url.path, "/foo/a%2Fb/hello%20world".to_string());
+        assert_eq!(
+            body.url.path_segments,
+            vec![
+                "foo".to_string(),
+                "a/b".to_string(),
+                "hello world".to_string()
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_method() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::delete()
+            .uri("/")
+            .send_request(&app)
+            .await;
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "DELETE".to_string());
+
+        let resp = test::TestRequest::patch().uri("/").send_request(&app).await;
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "PATCH".to_string());
+
+        let resp = test::TestRequest::post().uri("/").send_request(&app).await;
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "POST".to_string());
+
+        let resp = test::TestRequest::post().uri("/").send_request(&app).await;
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "POST".to_string());
+
+        let resp = test::TestRequest::put().uri("/").send_request(&app).await;
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "PUT".to_string());
+    }
+
+    #[rstest]
+    #[case("418", StatusCode::IM_A_TEAPOT)]
+    #[case("503", StatusCode::SERVICE_UNAVAILABLE)]
+    #[case("abc", StatusCode::OK)]
+    #[case("700", StatusCode::OK)]
+    #[actix_web::test]
+    async fn test_handler_returns_forced_status(#[case] value: &str, #[case] expected: StatusCode) {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri(&format!("/orders?__status={}", value))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), expected);
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.path, "/orders");
+        assert_eq!(body.query_param("__status"), Some(value));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_delays_response() {
+        let app = get_test_app().await;
+
+        let started = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__delay_ms=50")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_reports_dependency_chain() {
+        let app = get_test_app_with_settings(AppSettings {
+            dependency_chain: Some(dependencies::DependencyChain {
+                mode: dependencies::CallMode::Serial,
+                dependencies: vec![
+                    dependencies::Dependency {
+                        name: "db".to_string(),
+                        delay_ms: 30,
+                    },
+                    dependencies::Dependency {
+                        name: "cache".to_string(),
+                        delay_ms: 20,
+                    },
+                ],
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let started = Instant::now();
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.dependencies,
+            Some(vec![
+                DependencyCall {
+                    name: "db".to_string(),
+                    duration_ms: 30,
+                },
+                DependencyCall {
+                    name: "cache".to_string(),
+                    duration_ms: 20,
+                },
+            ])
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_caps_delay() {
+        let app = get_test_app_with_settings(AppSettings {
+            max_delay_ms: 10,
+            ..Default::default()
+        })
+        .await;
+
+        let started = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__delay_ms=60000")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_answers_options_requests() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::default()
+            .method(actix_web::http::Method::OPTIONS)
+            .uri("/orders")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let allow = resp.headers().get("Allow").unwrap().to_str().unwrap();
+        for method in ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"] {
+            assert!(allow.split(", ").any(|m| m == method), "missing {}", method);
+        }
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "OPTIONS");
+        assert_eq!(body.path, "/orders");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_answers_head_requests() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::default()
+            .method(actix_web::http::Method::HEAD)
+            .uri("/health")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // The service still produces the echo, actix-http drops it when writing the response.
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.method, "HEAD");
+
+        let (addr, handle) = start_test_server(AppState::default());
+        let received = raw_request(
+            addr,
+            "HEAD /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            usize::MAX,
+        )
+        .await;
+
+        let (head, body) = received.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(head.to_ascii_lowercase().contains("content-length: "));
+        assert_eq!(body, "");
+
+        handle.stop(true).await;
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_real_ip() {
+        let app = get_test_app().await;
+
+        let ip = "192.168.0.1";
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((X_FORWARDED_FOR, ip))
+            .peer_addr("127.0.0.1:12345".parse().unwrap())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.client.remote_ip, Some(ip.to_string()));
+    }
+
+    #[derive(Debug)]
+    struct StaticGeoLookup;
+
+    impl GeoLookup for StaticGeoLookup {
+        fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+            if ip != IpAddr::V4(Ipv4Addr::new(81, 2, 69, 160)) {
+                return None;
+            }
+
+            Some(GeoInfo {
+                country: Some("GB".to_string()),
+                city: Some("London".to_string()),
+                asn: Some(15169),
+            })
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_client_geo() {
+        let app = get_test_app_with_state(AppState {
+            geoip: Some(Box::new(StaticGeoLookup)),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .peer_addr("81.2.69.160:12345".parse().unwrap())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.client.geo,
+            Some(GeoInfo {
+                country: Some("GB".to_string()),
+                city: Some("London".to_string()),
+                asn: Some(15169),
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_no_geo_for_unknown_ip() {
+        let app = get_test_app_with_state(AppState {
+            geoip: Some(Box::new(StaticGeoLookup)),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .peer_addr("192.168.42.69:12345".parse().unwrap())
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.client.geo, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_headers() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Content-Type", "application/json"))
+            .insert_header(("X-Foo", "bar"))
+            .insert_header(("Authorization", "tRoLoLol"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        let mut expected = HashMap::new();
+        expected.insert(
+            "content-type".to_string(),
+            vec!["application/json".to_string()],
+        );
+        expected.insert("authorization".to_string(), vec!["tRoLoLol".to_string()]);
+        expected.insert("x-foo".to_string(), vec!["bar".to_string()]);
+
+        assert_eq!(body.headers, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_repeated_headers() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .append_header(("X-Foo", "first"))
+            .append_header(("X-Foo", "second"))
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.headers["x-foo"],
+            vec!["first".to_string(), "second".to_string()]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_batch_to_request_closing_it() {
+        let app = get_test_app_with_settings(AppSettings {
+            batching: Some(batch::Batching {
+                window_ms: 60000,
+                max_requests: 3,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        for path in ["/a", "/b"] {
+            let resp = test::TestRequest::get().uri(path).send_request(&app).await;
+
+            assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+            let body: CatchallResponse = test::read_body_json(resp).await;
+
+            assert_eq!(body.path, path);
+        }
+
+        let resp = test::TestRequest::get().uri("/c").send_request(&app).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let batch: Vec<CatchallResponse> = test::read_body_json(resp).await;
+
+        assert_eq!(
+            batch
+                .iter()
+                .map(|echo| echo.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/a", "/b", "/c"]
+        );
+
+        let resp = test::TestRequest::get().uri("/d").send_request(&app).await;
+
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_sets_response_headers_on_batch_responses() {
+        let app = get_test_app_with_settings(AppSettings {
+            batching: Some(batch::Batching {
+                window_ms: 60000,
+                max_requests: 2,
+            }),
+            mirror_echo_headers: true,
+            ..Default::default()
+        })
+        .await;
+
+        for expected in [StatusCode::ACCEPTED, StatusCode::OK] {
+            let resp = test::TestRequest::default()
+                .method(Method::OPTIONS)
+                .uri("/")
+                .insert_header(("X-Echo-Trace-Id", "abc"))
+                .send_request(&app)
+                .await;
+
+            assert_eq!(resp.status(), expected);
+            assert_eq!(resp.headers().get("trace-id").unwrap(), "abc");
+            assert_eq!(resp.headers().get(ALLOW).unwrap(), ALLOWED_METHODS);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_simulates_eventual_consistency() {
+        let app = get_test_app_with_settings(AppSettings {
+            consistency_delay_ms: Some(50),
+            ..Default::default()
+        })
+        .await;
+
+        let write = |value: Value| {
+            test::TestRequest::post()
+                .uri("/item")
+                .set_json(value)
+                .to_request()
+        };
+        let read = || test::TestRequest::get().uri("/item").to_request();
+
+        test::call_service(&app, write(serde_json::json!({"v": 1}))).await;
+        let resp = test::call_service(&app, read()).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        sleep(Duration::from_millis(60)).await;
+        test::call_service(&app, write(serde_json::json!({"v": 2}))).await;
+        let body: Value = test::call_and_read_body_json(&app, read()).await;
+
+        assert_eq!(body, serde_json::json!({"v": 1}));
+
+        sleep(Duration::from_millis(60)).await;
+        let body: Value = test::call_and_read_body_json(&app, read()).await;
+
+        assert_eq!(body, serde_json::json!({"v": 2}));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_cookies() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Cookie", "a=1; b=2"))
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.cookies,
+            HashMap::from([
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string()),
+            ])
+        );
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert!(body.cookies.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_cookie_details() {
+        let app = get_test_app_with_settings(AppSettings {
+            cookie_details: true,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Cookie", "session=\"abc%3D%3D\"; theme=dark"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        let cookies = body.cookie_details.unwrap();
+
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0].name, "session".to_string());
+        assert_eq!(cookies[0].value, "abc==".to_string());
+        assert!(cookies[0].quoted);
+        assert_eq!(cookies[1].name, "theme".to_string());
+        assert_eq!(cookies[1].value, "dark".to_string());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_omits_cookie_details_by_default() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("Cookie", "theme=dark"))
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.cookie_details, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_parsed_accept() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((
+                ACCEPT,
+                "text/*;q=0.5, */*;q=0.1, application/json, application/xml;q=0.9",
+            ))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        let accept: Vec<_> = body
+            .accept
+            .iter()
+            .map(|r| (r.media_type.as_str(), r.q))
+            .collect();
+
+        assert_eq!(
+            accept,
+            vec![
+                ("application/json", 1.0),
+                ("application/xml", 0.9),
+                ("text/*", 0.5),
+                ("*/*", 0.1),
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_query_params() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?foo=bar&baz=69")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        let mut expected = HashMap::new();
+        expected.insert("foo".to_string(), vec!["bar".to_string()]);
+        expected.insert("baz".to_string(), vec!["69".to_string()]);
+
+        assert_eq!(body.query_params, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_repeated_query_params() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?a=1&a=2&b=3&flag")
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.query_params,
+            HashMap::from([
+                ("a".to_string(), vec!["1".to_string(), "2".to_string()]),
+                ("b".to_string(), vec!["3".to_string()]),
+                ("flag".to_string(), vec!["".to_string()]),
+            ])
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_get_query_params_decodes_values() {
+        assert_eq!(get_query_params(""), HashMap::new());
+        assert_eq!(
+            get_query_params("q=hello+world%21&%C3%A9=%3D&empty="),
+            HashMap::from([
+                ("q".to_string(), vec!["hello world!".to_string()]),
+                ("é".to_string(), vec!["=".to_string()]),
+                ("empty".to_string(), vec!["".to_string()]),
+            ])
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_json_body() {
+        let app = get_test_app().await;
+
+        let payload = "{\"foo\": \"bar\"}";
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload(payload)
+            .insert_header(ContentType::json())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        let expected_json: Value = serde_json::from_str(payload).expect("Malformed json");
+        let expected_raw = "eyJmb28iOiAiYmFyIn0=".to_string();
+
+        assert_eq!(
+            body.body,
+            Body {
+                json: Some(expected_json),
+                json_depth: Some(1),
+                json_node_count: Some(2),
+                raw: expected_raw,
+                text: Some(payload.to_string()),
+                total_size: 14,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_json_shape() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload(r#"{"order": {"items": [{"id": 1}, {"id": 2}]}, "paid": true}"#)
+            .insert_header(ContentType::json())
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.json_depth, Some(4));
+        assert_eq!(body.body.json_node_count, Some(8));
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload("not json")
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.json_depth, None);
+        assert_eq!(body.body.json_node_count, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_strict_json_accepts_valid_json() {
+        let app = get_test_app_with_settings(AppSettings {
+            strict_json: true,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload("{\"foo\": \"bar\"}")
+            .insert_header(ContentType::json())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_strict_json_rejects_invalid_json() {
+        let app = get_test_app_with_settings(AppSettings {
+            strict_json: true,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload("{\n  \"foo\": bar\n}")
+            .insert_header(ContentType::json())
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let body: JsonErrorResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.line, 2);
+        assert_eq!(body.column, 10);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_lenient_json_accepts_invalid_json() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload("{\"foo\": bar}")
+            .insert_header(ContentType::json())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.json, None);
+    }
+
+    #[rstest]
+    #[case(r#"{"a": {"b": [1]}}"#.to_string(), JSON_CONTENT_TYPE, StatusCode::OK)]
+    #[case(r#"{"a": {"b": [[1]]}}"#.to_string(), JSON_CONTENT_TYPE, StatusCode::BAD_REQUEST)]
+    #[case(format!("{}{}", "[".repeat(200), "]".repeat(200)), JSON_CONTENT_TYPE, StatusCode::BAD_REQUEST)]
+    #[case("[[[[1]]]]".to_string(), "text/plain", StatusCode::OK)]
+    #[actix_web::test]
+    async fn test_handler_enforces_max_json_depth(
+        #[case] payload: String,
+        #[case] content_type: &str,
+        #[case] expected: StatusCode,
+    ) {
+        let app = get_test_app_with_settings(AppSettings {
+            max_json_depth: Some(3),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload(payload)
+            .insert_header((CONTENT_TYPE, content_type))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), expected);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_multipart_parts() {
+        let app = get_test_app().await;
+
+        let payload = "--XyZ\r\n\
+            Content-Disposition: form-data; name=\"field\"\r\n\
+            \r\n\
+            value\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"a.bin\"\r\n\
+            Content-Type: application/octet-stream\r\n\
+            \r\n\
+            0123456789\r\n\
+            --XyZ--\r\n";
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, "multipart/form-data; boundary=XyZ"))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body.multipart,
+            Some(MultipartInfo {
+                part_count: 2,
+                total_bytes: 15,
+                complete: true,
+                parts: vec![
+                    multipart::PartInfo {
+                        name: Some("field".to_string()),
+                        filename: None,
+                        content_type: None,
+                        size: 5,
+                        content: Some(b64engine.encode("value")),
+                    },
+                    multipart::PartInfo {
+                        name: Some("file".to_string()),
+                        filename: Some("a.bin".to_string()),
+                        content_type: Some("application/octet-stream".to_string()),
+                        size: 10,
+                        content: Some(b64engine.encode("0123456789")),
+                    },
+                ],
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_diff_against_baseline() {
+        let app = get_test_app_with_settings(AppSettings {
+            body_baselines: HashMap::from([(
+                "/orders".to_string(),
+                r#"{"id": 1, "status": "new", "items": []}"#.to_string(),
+            )]),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"id": 1, "status": "paid", "total": 10}"#)
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.diff,
+            Some(JsonDiff {
+                added: vec!["/total".to_string()],
+                removed: vec!["/items".to_string()],
+                changed: vec![diff::ChangedField {
+                    path: "/status".to_string(),
+                    baseline: serde_json::json!("new"),
+                    actual: serde_json::json!("paid"),
+                }],
+            })
+        );
+
+        let resp = test::TestRequest::post()
+            .uri("/other")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"id": 1}"#)
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.diff, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_stable_snapshot() {
+        let (addr, handle) = start_test_server(
+            AppState::new(AppSettings {
+                slo_budget_ms: Some(1000),
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+
+        let request = "POST /orders?__stable=1&b=2&a=1 HTTP/1.1\r\n\
+            Host: localhost\r\n\
+            Content-Type: application/json\r\n\
+            X-One: 1\r\n\
+            X-Two: 2\r\n\
+            X-Three: 3\r\n\
+            Content-Length: 9\r\n\
+            Connection: close\r\n\
+            \r\n\
+            {\"id\": 1}";
+        let first = raw_request(addr, request, usize::MAX).await;
+        let second = raw_request(addr, request, usize::MAX).await;
+        handle.stop(true).await;
+
+        let body = |received: &str| received.split_once("\r\n\r\n").unwrap().1.to_string();
+        assert_eq!(body(&first), body(&second));
+
+        let echo: Value = serde_json::from_str(&body(&first)).unwrap();
+        assert_eq!(echo["request_id"], "<request_id>");
+        assert_eq!(echo["client"]["port"], "<port>");
+        assert_eq!(echo["slo"]["actual_ms"], "<duration>");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_xml_when_preferred() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/orders?page=2")
+            .insert_header((ACCEPT, "application/json;q=0.9, application/xml"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), XML_CONTENT_TYPE);
+
+        let body = test::read_body(resp).await;
+        let mut reader = quick_xml::Reader::from_reader(body.as_ref());
+        let mut buf = Vec::new();
+        let mut root = None;
+        loop {
+            match reader.read_event_into(&mut buf).expect("well-formed XML") {
+                quick_xml::events::Event::Start(e) if root.is_none() => {
+                    root = Some(e.name().into_inner().to_string())
+                }
+                quick_xml::events::Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        assert_eq!(root.as_deref(), Some("request"));
+        assert!(String::from_utf8_lossy(&body).contains("<path>/orders</path>"));
+
+        let resp = test::TestRequest::get()
+            .uri("/orders")
+            .insert_header((ACCEPT, "*/*"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), JSON_CONTENT_TYPE);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_parses_msgpack_body() {
+        let app = get_test_app().await;
+        let payload = serde_json::json!({"id": 1, "tags": ["a"]});
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, MSGPACK_CONTENT_TYPE))
+            .set_payload(msgpack::encode(&payload).unwrap())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.json, Some(payload));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_msgpack() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?a=1")
+            .insert_header((ACCEPT, MSGPACK_CONTENT_TYPE))
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"id": 1}"#)
+            .send_request(&app)
+            .await;
+
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            MSGPACK_CONTENT_TYPE
+        );
+
+        let body = test::read_body(resp).await;
+        let body: CatchallResponse = rmp_serde::from_slice(&body).unwrap();
+
+        assert_eq!(body.method, "POST");
+        assert_eq!(body.path, "/orders");
+        assert_eq!(
+            body.query_params,
+            HashMap::from([("a".to_string(), vec!["1".to_string()])])
+        );
+        assert_eq!(body.body.json, Some(serde_json::json!({"id": 1})));
+    }
+
+    #[rstest]
+    #[case(None, JSON_CONTENT_TYPE, "default, no Accept header")]
+    #[case(
+        Some("text/html, */*"),
+        JSON_CONTENT_TYPE,
+        "default, no supported media type explicitly accepted"
+    )]
+    #[case(
+        Some("application/xml;q=0.5, application/json"),
+        JSON_CONTENT_TYPE,
+        "preferred over application/xml, application/msgpack"
+    )]
+    #[actix_web::test]
+    async fn test_handler_returns_negotiation_trace(
+        #[case] accept: Option<&str>,
+        #[case] chosen: &str,
+        #[case] reason: &str,
+    ) {
+        let app = get_test_app_with_settings(AppSettings {
+            negotiation_trace: true,
+            ..Default::default()
+        })
+        .await;
+
+        let mut req = test::TestRequest::get().uri("/");
+        if let Some(accept) = accept {
+            req = req.insert_header((ACCEPT, accept));
+        }
+        let resp = req.send_request(&app).await;
+
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), chosen);
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.negotiation,
+            Some(NegotiationInfo {
+                accept: accept.map(str::to_string),
+                supported: SERIALIZED_CONTENT_TYPES.map(str::to_string).to_vec(),
+                chosen: chosen.to_string(),
+                reason: reason.to_string(),
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_negotiation_trace_of_html() {
+        let app = get_test_app_with_settings(AppSettings {
+            negotiation_trace: true,
+            html_highlight: true,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((ACCEPT, "text/html,application/xml;q=0.9"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), HTML_CONTENT_TYPE);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+        assert!(body.contains("explicitly accepted"));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_included_fields() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?__fields=method,path,headers")
+            .insert_header(("x-foo", "bar"))
+            .send_request(&app)
+            .await;
+
+        let body: Value = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "method": "GET",
+                "path": "/",
+                "headers": {"x-foo": ["bar"]},
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_drops_excluded_fields() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?__exclude=body,headers")
+            .send_request(&app)
+            .await;
+
+        let body: Value = test::read_body_json(resp).await;
+        let fields = body.as_object().unwrap();
+
+        assert!(!fields.contains_key("body"));
+        assert!(!fields.contains_key("headers"));
+        assert_eq!(fields["method"], "GET");
+        assert_eq!(fields["path"], "/");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_renames_fields() {
+        let app = get_test_app_with_settings(AppSettings {
+            field_renames: HashMap::from([
+                ("method".to_string(), "httpMethod".to_string()),
+                ("path".to_string(), "url".to_string()),
+                ("url".to_string(), "location".to_string()),
+            ]),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/orders")
+            .send_request(&app)
+            .await;
+
+        let body: Value = test::read_body_json(resp).await;
+
+        assert_eq!(body["httpMethod"], "GET");
+        assert_eq!(body["url"], "/orders");
+        assert_eq!(body["location"]["path"], "/orders");
+        assert!(body.get("method").is_none());
+        assert!(body["request_id"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_jq_transform_output() {
+        let app = get_test_app_with_settings(AppSettings {
+            jq_transform: Some("{total: (.items | map(.price) | add)}".to_string()),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"items": [{"price": 2}, {"price": 3}]}"#)
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body, serde_json::json!({"total": 5}));
+
+        let resp = test::TestRequest::post()
+            .uri("/orders")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"items": 1}"#)
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = test::TestRequest::get()
+            .uri("/orders")
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        assert_eq!(body.path, "/orders");
+    }
+
+    #[actix_web::test]
+    async fn test_app_state_rejects_invalid_jq_transform() {
+        let settings = AppSettings {
+            jq_transform: Some(".items |".to_string()),
+            ..Default::default()
+        };
+
+        assert!(AppState::new(settings).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_static_response_body() {
+        let app = get_test_app_with_settings(AppSettings {
+            static_response_body: Some("<ok/>".to_string()),
+            static_response_content_type: "application/xml".to_string(),
+            ..Default::default()
+        })
+        .await;
+
+        for req in [
+            test::TestRequest::get().uri("/orders?__status=500"),
+            test::TestRequest::post().uri("/").set_payload("{}"),
+        ] {
+            let resp = req.send_request(&app).await;
+
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "application/xml");
+            assert!(resp.headers().contains_key(X_REQUEST_ID));
+            assert_eq!(test::read_body(resp).await, "<ok/>");
+        }
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header((X_REQUEST_ID, "trace-123"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.headers().get(X_REQUEST_ID).unwrap(), "trace-123");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_static_response_file() {
+        let path = std::env::temp_dir().join(format!(
+            "catchall-static-response-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{"status": "up"}"#).unwrap();
+
+        let app = get_test_app_with_settings(AppSettings {
+            static_response_file: Some(path.to_str().unwrap().to_string()),
+            ..Default::default()
+        })
+        .await;
+        std::fs::remove_file(&path).unwrap();
+
+        let resp = test::TestRequest::get()
+            .uri("/health")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), JSON_CONTENT_TYPE);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body, serde_json::json!({"status": "up"}));
+    }
+
+    #[rstest]
+    #[case(Some("{}"), Some("response.json"))]
+    #[case(None, Some("/nonexistent/response.json"))]
+    #[actix_web::test]
+    async fn test_app_state_rejects_invalid_static_response(
+        #[case] body: Option<&str>,
+        #[case] file: Option<&str>,
+    ) {
+        let settings = AppSettings {
+            static_response_body: body.map(str::to_string),
+            static_response_file: file.map(str::to_string),
+            ..Default::default()
+        };
+
+        assert!(AppState::new(settings).is_err());
+    }
+
+    #[rstest]
+    #[case("application/toml")]
+    #[case("text/toml")]
+    #[actix_web::test]
+    async fn test_handler_returns_toml_body(#[case] content_type: &str) {
+        let app = get_test_app().await;
+
+        let payload = "title = \"catchall\"\n\
+            \n\
+            [server]\n\
+            port = 8080\n\
+            hosts = [\"a\", \"b\"]\n";
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, content_type))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body.toml,
+            Some(serde_json::json!({
+                "title": "catchall",
+                "server": {"port": 8080, "hosts": ["a", "b"]},
+            }))
+        );
+        assert_eq!(body.body.raw, b64engine.encode(payload));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_form_body() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload("a=1&b=hello%20world&a=2&c=x+y%26z")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body.form,
+            Some(HashMap::from([
+                ("a".to_string(), vec!["1".to_string(), "2".to_string()]),
+                ("b".to_string(), vec!["hello world".to_string()]),
+                ("c".to_string(), vec!["x y&z".to_string()]),
+            ]))
+        );
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .set_payload("a=1")
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.form, None);
+    }
+
+    #[rstest]
+    #[case("text/ini")]
+    #[case("text/plain")]
+    #[actix_web::test]
+    async fn test_handler_returns_ini_body(#[case] content_type: &str) {
+        let app = get_test_app().await;
+
+        let payload = "; legacy settings\n\
+            [server]\n\
+            host = localhost\n\
+            port = 8080\n\
+            \n\
+            [log]\n\
+            level = debug\n";
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, content_type))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body.ini,
+            Some(HashMap::from([
+                (
+                    "server".to_string(),
+                    HashMap::from([
+                        ("host".to_string(), "localhost".to_string()),
+                        ("port".to_string(), "8080".to_string()),
+                    ])
+                ),
+                (
+                    "log".to_string(),
+                    HashMap::from([("level".to_string(), "debug".to_string())])
+                ),
+            ]))
+        );
+        assert_eq!(body.body.raw, b64engine.encode(payload));
+    }
+
+    #[rstest]
+    #[case("text/plain", "hello")]
+    #[case("text/plain", "[1, 2]")]
+    #[case("application/json", "[server]")]
+    #[actix_web::test]
+    async fn test_handler_ignores_non_ini_body(#[case] content_type: &str, #[case] payload: &str) {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, content_type))
+            .set_payload(payload.to_string())
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.ini, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_ignores_invalid_toml_body() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, "application/toml"))
+            .set_payload("title = ")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.toml, None);
+    }
+
+    fn deflate(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[rstest]
+    #[case("gzip", gzip(br#"{"foo": "bar"}"#))]
+    #[case("deflate", deflate(br#"{"foo": "bar"}"#))]
+    #[actix_web::test]
+    async fn test_handler_returns_decoded_with(#[case] encoding: &str, #[case] payload: Vec<u8>) {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .insert_header((CONTENT_ENCODING, encoding))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.decoded_with, Some(encoding.to_string()));
+        assert_eq!(body.body.json, Some(serde_json::json!({"foo": "bar"})));
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_ENCODING, "identity"))
+            .set_payload("foobar")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.decoded_with, None);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_text_raw_body_as_base64() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload("foobar")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body,
+            Body {
+                json: None,
+                raw: "Zm9vYmFy".to_string(),
+                text: Some("foobar".to_string()),
+                total_size: 6,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("text/plain", None)]
+    #[case("application/octet-stream", None)]
+    #[case(JSON_CONTENT_TYPE, Some(serde_json::json!(42)))]
+    #[case("application/problem+json", Some(serde_json::json!(42)))]
+    #[actix_web::test]
+    async fn test_handler_parses_json_only_for_json_content_types(
+        #[case] content_type: &str,
+        #[case] expected: Option<Value>,
+    ) {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, content_type))
+            .set_payload("42")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.json, expected);
+        assert_eq!(body.body.raw, "NDI=");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_truncates_large_bodies() {
+        let app = get_test_app_with_settings(AppSettings {
+            max_body_bytes: 4,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"foo": "bar"}"#)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body,
+            Body {
+                json: None,
+                raw: b64engine.encode(r#"{"fo"#),
+                text: Some(r#"{"fo"#.to_string()),
+                truncated: true,
+                total_size: 14,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_compressed_raw_body() {
+        let app = get_test_app_with_settings(AppSettings {
+            compress_raw_body: true,
+            ..Default::default()
+        })
+        .await;
+
+        let payload = "foobar".repeat(1000);
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload(payload.clone())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.raw_encoding, Some("gzip+base64".to_string()));
+
+        let compressed = b64engine.decode(&body.body.raw).unwrap();
+        assert!(compressed.len() < payload.len());
+
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, payload);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_binary_raw_body_as_base64() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload(vec![
+                35, 202, 75, 94, 123, 48, 108, 181, 224, 35, 30, 226, 172, 226, 125, 203, 201, 206,
+                88, 83, 172, 201, 188, 96, 30, 244, 44, 65, 6, 199, 135, 93,
+            ])
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body.body,
+            Body {
+                json: None,
+                raw: "I8pLXnswbLXgIx7irOJ9y8nOWFOsybxgHvQsQQbHh10=".to_string(),
+                text: None,
+                total_size: 32,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_pagination_links() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/items?__paginate=page=2,total=10")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let link = resp.headers().get(LINK).unwrap().to_str().unwrap();
+
+        assert!(link.contains("</items?__paginate=page=3,total=10>; rel=\"next\""));
+        assert!(link.contains("</items?__paginate=page=1,total=10>; rel=\"prev\""));
+        assert!(link.contains("</items?__paginate=page=1,total=10>; rel=\"first\""));
+        assert!(link.contains("</items?__paginate=page=10,total=10>; rel=\"last\""));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_split_chunks() {
+        let app = get_test_app_with_settings(AppSettings {
+            split_delay_ms: 1,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/foo?__split=chunks")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let body: Vec<Value> = test::read_body_json(resp).await;
+
+        assert!(body.len() > 1);
+        assert!(body.iter().all(|e| e.as_object().unwrap().len() == 1));
+        assert!(body.contains(&serde_json::json!({"method": "GET"})));
+        assert!(body.contains(&serde_json::json!({"path": "/foo"})));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_streams_infinite_body_until_disconnect() {
+        let (addr, handle) = start_test_server(AppState::default());
+
+        let received = raw_request(
+            addr,
+            "GET /?__infinite=1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            256 * 1024,
+        )
+        .await;
+
+        assert!(received.starts_with("HTTP/1.1 200 OK"));
+        assert!(received.len() >= 256 * 1024);
+        assert!(received.contains("\"seq\":1000"));
+
+        let stopped = timeout(Duration::from_secs(5), async {
+            while infinite::active_streams() > 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(stopped.is_ok());
+
+        let received = raw_request(
+            addr,
+            "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            usize::MAX,
+        )
+        .await;
+
+        assert!(received.starts_with("HTTP/1.1 200 OK"));
+
+        handle.stop(true).await;
+    }
+
+    #[actix_web::test]
+    async fn test_handler_signs_response() {
+        let app = get_test_app_with_settings(AppSettings {
+            response_signing_secret: Some("s3cr3t".to_string()),
+            response_signing_algorithm: signing::SigningAlgorithm::Sha512,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert!(resp.status().is_success());
+
+        let signature = resp.headers().get(SIGNATURE_HEADER).unwrap().clone();
+        let body = test::read_body(resp).await;
+
+        let mut mac = hmac::Hmac::<sha2::Sha512>::new_from_slice(b"s3cr3t").unwrap();
+        mac.update(&body);
+        let expected = format!("sha512={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert_eq!(signature.to_str().unwrap(), expected);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_signs_response_with_ed25519() {
+        let app = get_test_app_with_settings(AppSettings {
+            response_signing_ed25519_key: Some(b64engine.encode([7u8; 32])),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert!(resp.status().is_success());
+
+        let decode = |name: &str| b64engine.decode(resp.headers().get(name).unwrap()).unwrap();
+        let signature = Signature::from_slice(&decode(ED25519_SIGNATURE_HEADER)).unwrap();
+        let public_key: [u8; 32] = decode(PUBLIC_KEY_HEADER).try_into().unwrap();
+        let public_key = VerifyingKey::from_bytes(&public_key).unwrap();
+        let body = test::read_body(resp).await;
+
+        assert!(public_key.verify(&body, &signature).is_ok());
+        assert!(public_key.verify(b"tampered", &signature).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_does_not_sign_response_by_default() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert!(resp.headers().get(SIGNATURE_HEADER).is_none());
+        assert!(resp.headers().get(ED25519_SIGNATURE_HEADER).is_none());
+    }
+
+    fn latency_profiles_settings() -> AppSettings {
+        let mut latency_profiles = HashMap::new();
+        latency_profiles.insert(
+            "fast".to_string(),
+            latency::LatencyProfile {
+                delay_ms: 10,
+                jitter_ms: 10,
+                error_rate: 0.0,
+            },
+        );
+        latency_profiles.insert(
+            "slow".to_string(),
+            latency::LatencyProfile {
+                delay_ms: 200,
+                jitter_ms: 50,
+                error_rate: 0.0,
+            },
+        );
+        latency_profiles.insert(
+            "broken".to_string(),
+            latency::LatencyProfile {
+                error_rate: 1.0,
+                ..Default::default()
+            },
+        );
+
+        AppSettings {
+            latency_profiles,
+            ..Default::default()
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_applies_latency_profiles() {
+        let app = get_test_app_with_settings(latency_profiles_settings()).await;
+
+        let start = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=fast")
+            .send_request(&app)
+            .await;
+        let fast = start.elapsed();
+
+        assert!(resp.status().is_success());
+
+        let start = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=slow")
+            .send_request(&app)
+            .await;
+        let slow = start.elapsed();
+
+        assert!(resp.status().is_success());
+
+        assert!(fast >= Duration::from_millis(10));
+        assert!(fast < Duration::from_millis(200));
+        assert!(slow >= Duration::from_millis(200));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_applies_latency_profile_errors() {
+        let app = get_test_app_with_settings(latency_profiles_settings()).await;
+
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=broken")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=unknown")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_enforces_response_timeout() {
+        let mut settings = latency_profiles_settings();
+        settings.response_timeout_ms = Some(50);
+        let app = get_test_app_with_settings(settings).await;
+
+        let start = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=slow")
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert!(resp.status().is_success());
+    }
+
+    #[rstest]
+    #[case("fast", true)]
+    #[case("slow", false)]
+    #[actix_web::test]
+    async fn test_handler_returns_slo(#[case] profile: &str, #[case] within_budget: bool) {
+        let app = get_test_app_with_settings(AppSettings {
+            slo_budget_ms: Some(100),
+            ..latency_profiles_settings()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri(&format!("/?__profile={}", profile))
+            .send_request(&app)
+            .await;
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+        let slo = body.slo.unwrap();
+
+        assert_eq!(slo.budget_ms, 100);
+        assert_eq!(slo.within_budget, within_budget);
+        assert_eq!(slo.within_budget, slo.actual_ms <= 100);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_respects_client_deadline() {
+        let app = get_test_app_with_settings(latency_profiles_settings()).await;
+
+        let deadline = chrono::Utc::now() - chrono::Duration::seconds(1);
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("X-Deadline", deadline.to_rfc3339()))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let deadline = chrono::Utc::now() + chrono::Duration::milliseconds(50);
+        let start = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=slow")
+            .insert_header(("X-Deadline", deadline.to_rfc3339()))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=slow")
+            .insert_header(("grpc-timeout", "10m"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=fast")
+            .insert_header(("grpc-timeout", "5S"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let start = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/?__delay_ms=300")
+            .insert_header(("grpc-timeout", "50m"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        // Neither delay is past the deadline on its own, but their total is.
+        let resp = test::TestRequest::get()
+            .uri("/?__profile=fast&__delay_ms=40")
+            .insert_header(("grpc-timeout", "45m"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let mut settings = latency_profiles_settings();
+        settings.dependency_chain = Some(dependencies::DependencyChain {
+            mode: dependencies::CallMode::Serial,
+            dependencies: vec![dependencies::Dependency {
+                name: "db".to_string(),
+                delay_ms: 300,
+            }],
+        });
+        let app = get_test_app_with_settings(settings).await;
+
+        let start = Instant::now();
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("grpc-timeout", "50m"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        let app = get_test_app_with_settings(AppSettings {
+            delay_per_kb_ms: Some(300),
+            ..Default::default()
+        })
+        .await;
+
+        let start = Instant::now();
+        let resp = test::TestRequest::post()
+            .uri("/")
+            .set_payload(vec![b'a'; 1024])
+            .insert_header(("grpc-timeout", "50m"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(start.elapsed() < Duration::from_millis(200));
+    }
+
+    #[actix_web::test]
+    async fn test_max_connections_per_ip() {
+        let mut settings = latency_profiles_settings();
+        settings.max_connections_per_ip = Some(1);
+        let app = get_test_app_with_settings(settings).await;
+
+        let slow_request = |ip: &str| {
+            test::TestRequest::get()
+                .uri("/?__profile=slow")
+                .peer_addr(format!("{}:12345", ip).parse().unwrap())
+                .send_request(&app)
+        };
+
+        let (first, second, other) = futures_util::join!(
+            slow_request("192.168.0.1"),
+            slow_request("192.168.0.1"),
+            slow_request("192.168.0.2"),
+        );
+
+        let mut statuses = vec![first.status(), second.status()];
+        statuses.sort();
+
+        assert_eq!(
+            statuses,
+            vec![StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]
+        );
+        assert_eq!(other.status(), StatusCode::OK);
+
+        let resp = slow_request("192.168.0.1").await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[rstest]
+    #[case(retry_after::RetryAfterFormat::Seconds)]
+    #[case(retry_after::RetryAfterFormat::Date)]
+    #[actix_web::test]
+    async fn test_retry_after_on_too_many_requests(#[case] format: retry_after::RetryAfterFormat) {
+        let app = get_test_app_with_settings(AppSettings {
+            max_connections_per_ip: Some(0),
+            retry_after: Some(retry_after::RetryAfter {
+                seconds: 120,
+                format,
+            }),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .peer_addr("192.168.0.1:12345".parse().unwrap())
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let value = resp.headers().get(RETRY_AFTER).unwrap().to_str().unwrap();
+        match format {
+            retry_after::RetryAfterFormat::Seconds => assert_eq!(value, "120"),
+            retry_after::RetryAfterFormat::Date => {
+                let date =
+                    chrono::NaiveDateTime::parse_from_str(value, "%a, %d %b %Y %H:%M:%S GMT")
+                        .unwrap()
+                        .and_utc();
+                let wait = date - chrono::Utc::now();
+                assert!(wait > chrono::Duration::seconds(115));
+                assert!(wait <= chrono::Duration::seconds(120));
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_injects_fuzzed_headers() {
+        let fuzz_headers = fuzz::FuzzHeaders {
+            count: 3,
+            seed: Some(1234),
+            charset: "abcXYZ019-_".to_string(),
+        };
+        let expected = HeaderFuzzer::new(&fuzz_headers).unwrap().generate();
+        let app = get_test_app_with_settings(AppSettings {
+            fuzz_headers: Some(fuzz_headers),
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get().uri("/").send_request(&app).await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(expected.len(), 3);
+        for (name, value) in expected {
+            assert_eq!(resp.headers().get(name), Some(&value));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_injects_errors_by_path() {
+        let app = get_test_app_with_settings(AppSettings {
+            error_rate: 1.0,
+            path_error_rates: vec![
+                path_value::PathValue::new("/flaky/*", 1.0),
+                path_value::PathValue::new("/stable/*", 0.0),
+            ],
+            error_seed: Some(42),
+            ..Default::default()
+        })
+        .await;
+
+        for _ in 0..10 {
+            let flaky = test::TestRequest::get()
+                .uri("/flaky/orders")
+                .send_request(&app)
+                .await;
+            let stable = test::TestRequest::get()
+                .uri("/stable/orders")
+                .send_request(&app)
+                .await;
+            let unmatched = test::TestRequest::get()
+                .uri("/other")
+                .send_request(&app)
+                .await;
+
+            assert_eq!(flaky.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            assert_eq!(stable.status(), StatusCode::OK);
+            assert_eq!(unmatched.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_highlighted_html() {
+        let app = get_test_app_with_settings(AppSettings {
+            html_highlight: true,
+            ..Default::default()
+        })
+        .await;
+
+        let resp = test::TestRequest::get()
+            .uri("/foo")
+            .insert_header((ACCEPT, "text/html,application/xhtml+xml;q=0.9"))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), HTML_CONTENT_TYPE);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+        assert!(body.contains(
+            "<span class=\"key\">&quot;path&quot;</span>: <span class=\"string\">&quot;/foo&quot;</span>"
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_json_without_html_highlight() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::get()
+            .uri("/foo")
+            .insert_header((ACCEPT, "text/html"))
+            .send_request(&app)
+            .await;
+
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), JSON_CONTENT_TYPE);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_echoes_body_read_slowly() {
+        let app = get_test_app().await;
+        let payload = "x".repeat(500);
+
+        let start = std::time::Instant::now();
+        let resp = test::TestRequest::post()
+            .uri("/upload?__slow_read=1000")
+            .set_payload(payload.clone())
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert!(start.elapsed() >= Duration::from_millis(450));
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.body.raw, b64engine.encode(&payload));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_nginx_access_log() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?__echo=nginx")
+            .peer_addr("192.168.0.1:12345".parse().unwrap())
+            .insert_header(("User-Agent", "curl/8.0"))
+            .insert_header(("Referer", "https://example.com/"))
+            .set_payload("hello")
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            TEXT_PLAIN_CONTENT_TYPE
+        );
+
+        let line = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let (prefix, rest) = line.split_once(" [").unwrap();
+        let (time, rest) = rest.split_once("] ").unwrap();
+
+        assert_eq!(prefix, "192.168.0.1 - -");
+        assert!(chrono::DateTime::parse_from_str(time, "%d/%b/%Y:%H:%M:%S %z").is_ok());
+        assert_eq!(
+            rest,
+            "\"POST /orders?__echo=nginx HTTP/1.1\" 200 5 \"https://example.com/\" \"curl/8.0\""
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_log_event() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?__echo=logevent")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"id": 42}"#)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let event: Value = test::read_body_json(resp).await;
+
+        assert!(event["@timestamp"]
+            .as_str()
+            .is_some_and(|t| t.ends_with('Z')));
+        assert_eq!(event["message"], "POST /orders");
+        assert_eq!(event["host"], "localhost");
+        assert_eq!(event["request"]["method"], "POST");
+        assert_eq!(event["request"]["path"], "/orders");
+        assert_eq!(
+            event["request"]["body"]["json"],
+            serde_json::json!({"id": 42})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_amqp_message() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?__echo=amqp")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .insert_header(("X-Tenant", "acme"))
+            .set_payload(r#"{"id": 42}"#)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let message: Value = test::read_body_json(resp).await;
+        let properties = &message["properties"];
+
+        assert_eq!(properties["content_type"], JSON_CONTENT_TYPE);
+        assert_eq!(properties["headers"]["x-tenant"], "acme");
+        assert_eq!(properties["headers"]["x-http-method"], "POST");
+        assert_eq!(properties["headers"]["x-http-path"], "/orders");
+        assert!(properties["message_id"].is_string());
+        assert!(properties["timestamp"].is_i64());
+        assert_eq!(message["body"], b64engine.encode(r#"{"id": 42}"#));
+        assert_eq!(message["body_encoding"], "base64");
+    }
+
+    #[actix_web::test]
+    async fn test_handler_echoes_raw_json_body() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?__echo=1")
+            .insert_header((CONTENT_TYPE, "application/vnd.api+json"))
+            .set_payload(r#"{ "id": 42 }"#)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "application/vnd.api+json"
+        );
+        assert_eq!(test::read_body(resp).await, r#"{ "id": 42 }"#);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_echoes_raw_binary_body() {
+        let app = get_test_app().await;
+        let payload: &[u8] = &[0x00, 0xff, 0x10, 0x80, 0xc3];
+
+        let resp = test::TestRequest::post()
+            .uri("/upload")
+            .insert_header((CONTENT_TYPE, "image/png"))
+            .insert_header(("X-Catchall-Echo", "true"))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "image/png");
+        assert_eq!(test::read_body(resp).await, payload);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_api_gateway_event() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?__echo=apigw&tag=a&tag=b")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"id": 42}"#)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+
+        let event: Value = test::read_body_json(resp).await;
+
+        assert_eq!(event["httpMethod"], "POST");
+        assert_eq!(event["path"], "/orders");
+        assert_eq!(event["headers"]["content-type"], JSON_CONTENT_TYPE);
+        assert_eq!(event["queryStringParameters"]["tag"], "b");
+        assert_eq!(
+            event["multiValueQueryStringParameters"]["tag"],
+            serde_json::json!(["a", "b"])
+        );
+        assert_eq!(event["body"], r#"{"id": 42}"#);
+        assert_eq!(event["isBase64Encoded"], false);
+        assert!(event["requestContext"]["requestId"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_download() {
+        let app = get_test_app().await;
+        let payload: &[u8] = &[0x00, 0xff, 0x10, b'\r', b'\n', 0x80];
+
+        let resp = test::TestRequest::post()
+            .uri("/upload?__echo=download")
+            .insert_header((CONTENT_TYPE, "application/octet-stream"))
+            .set_payload(payload)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            DOWNLOAD_CONTENT_TYPE
+        );
+        assert_eq!(
+            resp.headers().get("Content-Disposition").unwrap(),
+            "attachment; filename=\"request.bin\""
+        );
+        assert_eq!(test::read_body(resp).await, payload);
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_cloud_event() {
+        let app = get_test_app().await;
+
+        let resp = test::TestRequest::post()
+            .uri("/orders?__echo=event")
+            .insert_header((CONTENT_TYPE, JSON_CONTENT_TYPE))
+            .set_payload(r#"{"id": 42}"#)
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            CLOUDEVENTS_CONTENT_TYPE
+        );
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
-struct CatchallResponse {
-    method: String,
-    path: String,
-    client: ClientInfo,
-    url: UrlInfo,
-    headers: HashMap<String, String>,
-    query_params: HashMap<String, String>,
-    body: Body,
-}
+        let event: Value = test::read_body_json(resp).await;
 
-async fn handler(
-    req: HttpRequest,
-    bytes: web::Bytes,
-    query: web::Query<HashMap<String, String>>,
-) -> Result<impl Responder> {
-    let method = req.method();
-    let path = req.path();
-    let client_info = get_client(&req);
-    let url_info = get_url_info(&req);
-    let headers = get_headers(&req);
-    let body = get_body(bytes);
+        for field in ["specversion", "type", "source", "id", "time"] {
+            assert!(event[field].is_string(), "missing {}", field);
+        }
+        assert_eq!(event["specversion"], "1.0");
 
-    let resp = CatchallResponse {
-        method: method.to_string(),
-        path: path.to_string(),
-        client: client_info,
-        url: url_info,
-        headers,
-        query_params: query.0,
-        body,
-    };
+        let data: CatchallResponse = serde_json::from_value(event["data"].clone()).unwrap();
 
-    info!(
-        "{} {}\n{}",
-        method,
-        path,
-        serde_json::to_string_pretty(&resp).expect("Error dumping resp to json")
-    );
+        assert_eq!(event["id"], data.request_id);
+        assert_eq!(data.method, "POST");
+        assert_eq!(data.path, "/orders");
+        assert_eq!(data.body.json, Some(serde_json::json!({"id": 42})));
+    }
 
-    let resp = web::Json(resp);
+    #[actix_web::test]
+    async fn test_handler_returns_challenge() {
+        let app = get_test_app_with_settings(AppSettings {
+            challenge_realm: "tests".to_string(),
+            ..Default::default()
+        })
+        .await;
 
-    Ok(resp)
-}
+        for (scheme, expected) in [
+            ("Basic", "Basic realm=\"tests\""),
+            ("Bearer", "Bearer realm=\"tests\""),
+            ("Digest", "Digest realm=\"tests\""),
+        ] {
+            let resp = test::TestRequest::get()
+                .uri(&format!("/?__challenge={}", scheme))
+                .insert_header(("Authorization", "Bearer valid-token"))
+                .send_request(&app)
+                .await;
 
-fn get_client(request: &HttpRequest) -> ClientInfo {
-    let conn_info = request.connection_info();
-    let remote_ip = conn_info.realip_remote_addr().map(|s| s.to_string());
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 
-    // Will only return None when called in unit tests unless TestRequest::peer_addr is used.
-    let port = request
-        .peer_addr()
-        .unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080))
-        .port();
+            let challenge = resp.headers().get(WWW_AUTHENTICATE).unwrap();
 
-    ClientInfo { remote_ip, port }
-}
+            assert!(challenge.to_str().unwrap().starts_with(expected));
+        }
+    }
 
-fn get_url_info(request: &HttpRequest) -> UrlInfo {
-    let conn_info = request.connection_info();
+    #[actix_web::test]
+    async fn test_handler_ignores_unknown_challenge() {
+        let app = get_test_app().await;
 
-    let host = conn_info.host();
-    let hostname = host.split(':').next().unwrap_or("").to_string();
-    let port = host
-        .split(':')
-        .nth(1)
-        .map(|p| p.parse::<u16>().unwrap_or(0))
-        .unwrap_or(0);
+        let resp = test::TestRequest::get()
+            .uri("/?__challenge=Nope")
+            .send_request(&app)
+            .await;
 
-    UrlInfo {
-        scheme: conn_info.scheme().to_string(),
-        hostname,
-        port,
-        path: request.path().to_string(),
+        assert!(resp.status().is_success());
+        assert!(resp.headers().get(WWW_AUTHENTICATE).is_none());
     }
-}
 
-fn get_headers(request: &HttpRequest) -> HashMap<String, String> {
-    request
-        .headers()
-        .iter()
-        .map(|(n, v)| (n.to_string(), v.to_str().unwrap_or("").to_string()))
-        .collect()
-}
+    #[rstest]
+    #[case(AppSettings::default(), "/__health")]
+    #[case(AppSettings { health_path: "/healthz".to_string(), ..Default::default() }, "/healthz")]
+    #[actix_web::test]
+    async fn test_health(#[case] settings: AppSettings, #[case] path: &str) {
+        let app = get_test_app_with_settings(settings).await;
 
-fn get_body(bytes: web::Bytes) -> Body {
-    let json: Option<Value> = serde_json::from_slice(&bytes).ok();
-    let raw = b64engine.encode(bytes);
+        let resp = test::TestRequest::get().uri(path).send_request(&app).await;
 
-    Body { json, raw }
-}
+        assert_eq!(resp.status(), StatusCode::OK);
 
-fn configure_app(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::resource("{path:.*}")
-            .route(web::delete().to(handler))
-            .route(web::get().to(handler))
-            .route(web::patch().to(handler))
-            .route(web::post().to(handler))
-            .route(web::put().to(handler)),
-    );
-}
+        let body: Value = test::read_body_json(resp).await;
 
-#[derive(Clone, Debug, Deserialize)]
-struct AppSettings {
-    host: String,
-    port: u16,
-    workers: usize,
-}
+        assert_eq!(body, serde_json::json!({"status": "ok"}));
+        assert!(serde_json::from_value::<CatchallResponse>(body).is_err());
+    }
 
-fn get_config() -> Result<Config, ConfigError> {
-    let env_source = config::Environment::with_prefix("CATCHALL_API");
-    Ok(Config::builder()
-        .set_default("host", "0.0.0.0")?
-        .set_default("port", 8080)?
-        .set_default("workers", 2)?
-        .add_source(env_source)
-        .build()
-        .unwrap())
-}
+    #[actix_web::test]
+    async fn test_history_returns_latest_requests() {
+        let app = get_test_app_with_settings(AppSettings {
+            history_size: 2,
+            ..Default::default()
+        })
+        .await;
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    log_rs::init(LogConfig::from_env().expect("Unable to initialize log config from env"));
+        for uri in ["/a", "/b", "/c"] {
+            test::TestRequest::get().uri(uri).send_request(&app).await;
+        }
+        test::TestRequest::get()
+            .uri(HISTORY_PATH)
+            .send_request(&app)
+            .await;
 
-    let settings: AppSettings = get_config()
-        .expect("valid config")
-        .try_deserialize()
-        .expect("valid config");
+        let resp = test::TestRequest::get()
+            .uri(HISTORY_PATH)
+            .send_request(&app)
+            .await;
 
-    info!("Starting server on {}:{}", settings.host, settings.port);
-    HttpServer::new(|| App::new().configure(configure_app).wrap(Logger::default()))
-        .workers(settings.workers)
-        .bind((settings.host, settings.port))?
-        .run()
-        .await
-}
+        assert!(resp.status().is_success());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use actix_http::Request;
-    use actix_web::{
-        body::BoxBody,
-        dev::{Service, ServiceResponse},
-        http::header::{ContentType, X_FORWARDED_FOR},
-        test,
-    };
+        let body: Vec<CatchallResponse> = test::read_body_json(resp).await;
 
-    async fn get_test_app(
-    ) -> impl Service<Request, Response = ServiceResponse<BoxBody>, Error = actix_web::Error> {
-        test::init_service(App::new().configure(configure_app)).await
+        assert_eq!(
+            body.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(),
+            vec!["/c", "/b"]
+        );
     }
 
     #[actix_web::test]
-    async fn test_handler_empty_request() {
+    async fn test_requests_returns_page_of_history() {
         let app = get_test_app().await;
 
+        for uri in ["/a", "/b", "/c", "/d", "/e"] {
+            test::TestRequest::get().uri(uri).send_request(&app).await;
+        }
+
         let resp = test::TestRequest::get()
-            .uri("/")
-            .peer_addr("192.168.42.69:12345".parse().unwrap())
+            .uri(&format!("{}?page=2&per_page=2", REQUESTS_PATH))
             .send_request(&app)
             .await;
 
         assert!(resp.status().is_success());
 
-        let body: CatchallResponse = test::read_body_json(resp).await;
-
-        let expected = CatchallResponse {
-            method: "GET".to_string(),
-            path: "/".to_string(),
-            client: ClientInfo {
-                remote_ip: Some("192.168.42.69".to_string()),
-                port: 12345,
-            },
-            url: UrlInfo {
-                scheme: "http".to_string(),
-                hostname: "localhost".to_string(),
-                port: 8080,
-                path: "/".to_string(),
-            },
-            ..Default::default()
-        };
+        let body: history::HistoryPage = test::read_body_json(resp).await;
 
-        assert_eq!(body, expected);
+        assert_eq!(body.total, 5);
+        assert_eq!(body.page, 2);
+        assert_eq!(body.per_page, 2);
+        assert_eq!(body.total_pages, 3);
+        assert_eq!(
+            body.requests
+                .iter()
+                .map(|r| r["path"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["/c", "/b"]
+        );
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_path() {
+    async fn test_path_stats() {
         let app = get_test_app().await;
+
+        for uri in ["/a", "/a?__challenge=Basic", "/b"] {
+            test::TestRequest::get().uri(uri).send_request(&app).await;
+        }
+
         let resp = test::TestRequest::get()
-            .uri("/foo/bar?baz=1")
+            .uri(STATS_PATH)
             .send_request(&app)
             .await;
 
         assert!(resp.status().is_success());
 
-        let body: CatchallResponse = test::read_body_json(resp).await;
+        let body: HashMap<String, stats::PathStats> = test::read_body_json(resp).await;
 
-        assert_eq!(body.path, "/foo/bar".to_string());
+        assert_eq!(body.len(), 2);
+        assert_eq!(body["/a"].count, 2);
+        assert_eq!(
+            body["/a"].statuses,
+            std::collections::BTreeMap::from([(200, 1), (401, 1)])
+        );
+        assert!(body["/a"].min_latency_ms <= body["/a"].max_latency_ms);
+        assert_eq!(body["/b"].count, 1);
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_method() {
-        let app = get_test_app().await;
+    async fn test_path_stats_skip_internal_paths() {
+        let app = get_test_app_with_settings(AppSettings {
+            health_path: "/health".to_string(),
+            ..Default::default()
+        })
+        .await;
 
-        let resp = test::TestRequest::delete()
-            .uri("/")
+        for uri in ["/health", "/health", METRICS_PATH, HISTORY_PATH, "/a"] {
+            test::TestRequest::get().uri(uri).send_request(&app).await;
+        }
+
+        let resp = test::TestRequest::get()
+            .uri(STATS_PATH)
             .send_request(&app)
             .await;
-        let body: CatchallResponse = test::read_body_json(resp).await;
-        assert_eq!(body.method, "DELETE".to_string());
+        let body: HashMap<String, stats::PathStats> = test::read_body_json(resp).await;
 
-        let resp = test::TestRequest::patch().uri("/").send_request(&app).await;
-        let body: CatchallResponse = test::read_body_json(resp).await;
-        assert_eq!(body.method, "PATCH".to_string());
+        assert_eq!(body.keys().collect::<Vec<_>>(), vec!["/a"]);
+    }
 
-        let resp = test::TestRequest::post().uri("/").send_request(&app).await;
-        let body: CatchallResponse = test::read_body_json(resp).await;
-        assert_eq!(body.method, "POST".to_string());
+    #[actix_web::test]
+    async fn test_oauth_token_issues_token() {
+        let app = get_test_app_with_settings(AppSettings {
+            oauth_token: oauth::OAuthToken {
+                access_token: "abc".to_string(),
+                expires_in: 60,
+                refresh_token: Some("def".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
 
-        let resp = test::TestRequest::post().uri("/").send_request(&app).await;
-        let body: CatchallResponse = test::read_body_json(resp).await;
-        assert_eq!(body.method, "POST".to_string());
+        let resp = test::TestRequest::post()
+            .uri(OAUTH_TOKEN_PATH)
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload("grant_type=client_credentials&client_id=app")
+            .send_request(&app)
+            .await;
 
-        let resp = test::TestRequest::put().uri("/").send_request(&app).await;
-        let body: CatchallResponse = test::read_body_json(resp).await;
-        assert_eq!(body.method, "PUT".to_string());
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Cache-Control").unwrap(), "no-store");
+
+        let body: oauth::TokenResponse = test::read_body_json(resp).await;
+
+        assert_eq!(
+            body,
+            oauth::TokenResponse {
+                access_token: "abc".to_string(),
+                token_type: "Bearer".to_string(),
+                expires_in: 60,
+                refresh_token: Some("def".to_string()),
+                scope: None,
+            }
+        );
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_real_ip() {
+    async fn test_oauth_token_rejects_unsupported_grant_type() {
         let app = get_test_app().await;
 
-        let ip = "192.168.0.1";
-
-        let resp = test::TestRequest::get()
-            .uri("/")
-            .insert_header((X_FORWARDED_FOR, ip))
-            .peer_addr("127.0.0.1:12345".parse().unwrap())
+        let resp = test::TestRequest::post()
+            .uri(OAUTH_TOKEN_PATH)
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code")
             .send_request(&app)
             .await;
 
-        assert!(resp.status().is_success());
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 
-        let body: CatchallResponse = test::read_body_json(resp).await;
+        let body: oauth::TokenError = test::read_body_json(resp).await;
 
-        assert_eq!(body.client.remote_ip, Some(ip.to_string()));
+        assert_eq!(body.error, "unsupported_grant_type");
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_headers() {
+    async fn test_metrics_count_requests() {
         let app = get_test_app().await;
 
+        for (method, uri) in [
+            (actix_web::http::Method::GET, "/a"),
+            (actix_web::http::Method::GET, "/b?__status=404"),
+            (actix_web::http::Method::POST, "/a"),
+        ] {
+            test::TestRequest::default()
+                .method(method)
+                .uri(uri)
+                .send_request(&app)
+                .await;
+        }
+
         let resp = test::TestRequest::get()
-            .uri("/")
-            .insert_header(("Content-Type", "application/json"))
-            .insert_header(("X-Foo", "bar"))
-            .insert_header(("Authorization", "tRoLoLol"))
+            .uri(METRICS_PATH)
             .send_request(&app)
             .await;
 
         assert!(resp.status().is_success());
 
-        let body: CatchallResponse = test::read_body_json(resp).await;
-
-        let mut expected = HashMap::new();
-        expected.insert("content-type".to_string(), "application/json".to_string());
-        expected.insert("authorization".to_string(), "tRoLoLol".to_string());
-        expected.insert("x-foo".to_string(), "bar".to_string());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let lines: Vec<_> = body.lines().collect();
 
-        assert_eq!(body.headers, expected);
+        assert!(lines.contains(&"catchall_requests_total 3"));
+        assert!(lines.contains(&"catchall_requests_by_method_total{method=\"GET\"} 2"));
+        assert!(lines.contains(&"catchall_requests_by_method_total{method=\"POST\"} 1"));
+        assert!(lines.contains(&"catchall_responses_total{class=\"2xx\"} 2"));
+        assert!(lines.contains(&"catchall_responses_total{class=\"4xx\"} 1"));
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_query_params() {
+    async fn test_help_lists_special_params() {
         let app = get_test_app().await;
 
         let resp = test::TestRequest::get()
-            .uri("/?foo=bar&baz=69")
+            .uri(HELP_PATH)
             .send_request(&app)
             .await;
 
         assert!(resp.status().is_success());
 
-        let body: CatchallResponse = test::read_body_json(resp).await;
-
-        let mut expected = HashMap::new();
-        expected.insert("foo".to_string(), "bar".to_string());
-        expected.insert("baz".to_string(), "69".to_string());
+        let body: Vec<Value> = test::read_body_json(resp).await;
+        let described = |name: &str| {
+            body.iter().any(|p| {
+                p["name"] == name && p["description"].as_str().is_some_and(|d| !d.is_empty())
+            })
+        };
 
-        assert_eq!(body.query_params, expected);
+        assert!(described("__profile"));
+        assert!(described("__echo"));
+        assert!(described("__slow_read"));
+        assert!(described("__status"));
+        assert!(described("__delay_ms"));
+        assert!(described("__stable"));
+        assert!(described("__fields"));
+        assert!(described("__exclude"));
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_json_body() {
+    async fn test_handler_returns_malformed_json() {
         let app = get_test_app().await;
 
-        let payload = "{\"foo\": \"bar\"}";
-
-        let resp = test::TestRequest::post()
-            .uri("/")
-            .set_payload(payload)
-            .insert_header(ContentType::json())
+        let resp = test::TestRequest::get()
+            .uri("/?__bad=json")
             .send_request(&app)
             .await;
 
-        assert!(resp.status().is_success());
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), JSON_CONTENT_TYPE);
 
-        let body: CatchallResponse = test::read_body_json(resp).await;
+        let body = test::read_body(resp).await;
 
-        let expected_json: Value = serde_json::from_str(payload).expect("Malformed json");
-        let expected_raw = "eyJmb28iOiAiYmFyIn0=".to_string();
+        assert!(serde_json::from_slice::<Value>(&body).is_err());
+    }
 
-        assert_eq!(
-            body.body,
-            Body {
-                json: Some(expected_json),
-                raw: expected_raw
+    #[actix_web::test]
+    async fn test_handler_redirect_loop() {
+        let app = get_test_app().await;
+
+        let mut uri = "/loop?__loop=5&foo=bar".to_string();
+        let mut redirects = 0;
+        let resp = loop {
+            let resp = test::TestRequest::get().uri(&uri).send_request(&app).await;
+            if resp.status() != StatusCode::FOUND {
+                break resp;
             }
-        );
+
+            redirects += 1;
+            uri = resp
+                .headers()
+                .get(LOCATION)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string();
+            assert!(redirects <= 5, "redirect loop didn't terminate");
+        };
+
+        assert_eq!(redirects, 5);
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: CatchallResponse = test::read_body_json(resp).await;
+
+        assert_eq!(body.path, "/loop".to_string());
+        assert_eq!(body.query_param("__loop"), Some("0"));
+        assert_eq!(body.query_param("foo"), Some("bar"));
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_text_raw_body_as_base64() {
+    async fn test_handler_accepts_expectations_by_default() {
         let app = get_test_app().await;
 
         let resp = test::TestRequest::post()
             .uri("/")
+            .insert_header((EXPECT, "100-continue"))
             .set_payload("foobar")
             .send_request(&app)
             .await;
 
         assert!(resp.status().is_success());
-
-        let body: CatchallResponse = test::read_body_json(resp).await;
-
-        assert_eq!(
-            body.body,
-            Body {
-                json: None,
-                raw: "Zm9vYmFy".to_string()
-            }
-        );
     }
 
     #[actix_web::test]
-    async fn test_handler_returns_binary_raw_body_as_base64() {
-        let app = get_test_app().await;
+    async fn test_handler_rejects_expectations() {
+        let app = get_test_app_with_settings(AppSettings {
+            reject_expectations: true,
+            ..Default::default()
+        })
+        .await;
 
         let resp = test::TestRequest::post()
             .uri("/")
-            .set_payload(vec![
-                35, 202, 75, 94, 123, 48, 108, 181, 224, 35, 30, 226, 172, 226, 125, 203, 201, 206,
-                88, 83, 172, 201, 188, 96, 30, 244, 44, 65, 6, 199, 135, 93,
-            ])
+            .insert_header((EXPECT, "100-continue"))
+            .set_payload("foobar")
             .send_request(&app)
             .await;
 
-        assert!(resp.status().is_success());
-
-        let body: CatchallResponse = test::read_body_json(resp).await;
-
-        assert_eq!(
-            body.body,
-            Body {
-                json: None,
-                raw: "I8pLXnswbLXgIx7irOJ9y8nOWFOsybxgHvQsQQbHh10=".to_string()
-            }
-        );
+        assert_eq!(resp.status(), StatusCode::EXPECTATION_FAILED);
     }
 }
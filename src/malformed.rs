@@ -0,0 +1,33 @@
+use crate::help::SpecialParam;
+
+pub const BAD_PARAM: &str = "__bad";
+pub const BAD_JSON: &str = "json";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: BAD_PARAM,
+    value: "json",
+    description: "Drops the closing brace of the JSON body, while still answering `200`",
+}];
+
+/// Intentionally breaks a serialized JSON document by dropping its closing brace or bracket, to
+/// test how clients handle unparseable bodies on successful responses.
+pub fn break_json(body: &mut Vec<u8>) {
+    if matches!(body.last(), Some(b'}') | Some(b']')) {
+        body.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_break_json() {
+        let mut body = b"{\"foo\":\"bar\"}".to_vec();
+
+        break_json(&mut body);
+
+        assert_eq!(body, b"{\"foo\":\"bar\"".to_vec());
+        assert!(serde_json::from_slice::<serde_json::Value>(&body).is_err());
+    }
+}
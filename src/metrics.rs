@@ -0,0 +1,120 @@
+use actix_web::{http::StatusCode, web, HttpResponse};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::AppState;
+
+pub const METRICS_PATH: &str = "/__metrics";
+
+/// Prometheus text exposition format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Counters of the requests handled by the catchall, exposed in the Prometheus format.
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    total: AtomicU64,
+    by_method: Mutex<BTreeMap<String, u64>>,
+    /// Responses by status class, `1xx` to `5xx`.
+    by_status_class: [AtomicU64; 5],
+}
+
+impl RequestMetrics {
+    pub fn record(&self, method: &str, status: StatusCode) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        *self
+            .by_method
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_insert(0) += 1;
+        if let Some(counter) = self
+            .by_status_class
+            .get((status.as_u16() / 100).saturating_sub(1) as usize)
+        {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut counter = |name: &str, help: &str, samples: Vec<(String, u64)>| {
+            writeln!(out, "# HELP {} {}", name, help).expect("writing to a String");
+            writeln!(out, "# TYPE {} counter", name).expect("writing to a String");
+            for (labels, value) in samples {
+                writeln!(out, "{}{} {}", name, labels, value).expect("writing to a String");
+            }
+        };
+
+        counter(
+            "catchall_requests_total",
+            "Requests handled by the catchall.",
+            vec![(String::new(), self.total.load(Ordering::Relaxed))],
+        );
+        counter(
+            "catchall_requests_by_method_total",
+            "Requests handled by the catchall, by method.",
+            self.by_method
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(method, count)| (format!("{{method=\"{}\"}}", method), *count))
+                .collect(),
+        );
+        counter(
+            "catchall_responses_total",
+            "Responses of the catchall, by status class.",
+            self.by_status_class
+                .iter()
+                .enumerate()
+                .map(|(i, count)| {
+                    (
+                        format!("{{class=\"{}xx\"}}", i + 1),
+                        count.load(Ordering::Relaxed),
+                    )
+                })
+                .collect(),
+        );
+
+        out
+    }
+}
+
+pub async fn metrics(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(state.metrics.render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = RequestMetrics::default();
+
+        metrics.record("GET", StatusCode::OK);
+        metrics.record("GET", StatusCode::NOT_FOUND);
+        metrics.record("POST", StatusCode::CREATED);
+
+        assert_eq!(
+            metrics.render(),
+            "# HELP catchall_requests_total Requests handled by the catchall.\n\
+             # TYPE catchall_requests_total counter\n\
+             catchall_requests_total 3\n\
+             # HELP catchall_requests_by_method_total Requests handled by the catchall, by method.\n\
+             # TYPE catchall_requests_by_method_total counter\n\
+             catchall_requests_by_method_total{method=\"GET\"} 2\n\
+             catchall_requests_by_method_total{method=\"POST\"} 1\n\
+             # HELP catchall_responses_total Responses of the catchall, by status class.\n\
+             # TYPE catchall_responses_total counter\n\
+             catchall_responses_total{class=\"1xx\"} 0\n\
+             catchall_responses_total{class=\"2xx\"} 2\n\
+             catchall_responses_total{class=\"3xx\"} 0\n\
+             catchall_responses_total{class=\"4xx\"} 1\n\
+             catchall_responses_total{class=\"5xx\"} 0\n"
+        );
+    }
+}
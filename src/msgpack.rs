@@ -0,0 +1,53 @@
+use serde::Serialize;
+use serde_json::Value;
+
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+/// Former content type of MessagePack, before `application/msgpack` was registered.
+const MSGPACK_LEGACY_CONTENT_TYPE: &str = "application/x-msgpack";
+
+pub fn is_msgpack(content_type: &str) -> bool {
+    content_type == MSGPACK_CONTENT_TYPE || content_type == MSGPACK_LEGACY_CONTENT_TYPE
+}
+
+/// Decodes a MessagePack body into its JSON equivalent. Bodies with binary values or with maps keyed
+/// by anything else than strings have none.
+pub fn decode(bytes: &[u8]) -> Option<Value> {
+    rmp_serde::from_slice(bytes).ok()
+}
+
+/// Encodes structs as maps keyed by field name, rather than as arrays, so that the output can be
+/// inspected without the schema.
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec_named(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_encode_and_decode() {
+        let value =
+            json!({"id": 1, "tags": ["a", "b"], "price": 9.99, "active": true, "note": null});
+
+        assert_eq!(decode(&encode(&value).unwrap()), Some(value));
+    }
+
+    #[test]
+    fn test_encode_names_fields() {
+        #[derive(Serialize)]
+        struct Item {
+            id: u8,
+        }
+
+        // fixmap of 1 entry, "id" as a fixstr of 2 bytes, 1 as a positive fixint.
+        assert_eq!(encode(&Item { id: 1 }).unwrap(), b"\x81\xa2id\x01");
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_bodies() {
+        assert_eq!(decode(b"\xc1"), None);
+        assert_eq!(decode(b"\x81\x01\x02"), None);
+    }
+}
@@ -0,0 +1,196 @@
+use base64::{engine::general_purpose::STANDARD as b64engine, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// Parts up to this size have their content echoed.
+const SMALL_PART_BYTES: usize = 1024;
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PartInfo {
+    /// Field name, from the `Content-Disposition` of the part.
+    pub name: Option<String>,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub size: usize,
+    /// Base64 of the content, for parts of at most 1 KiB.
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MultipartInfo {
+    pub part_count: usize,
+    /// Sum of the sizes of the parts' contents, excluding their headers and the boundaries.
+    pub total_bytes: usize,
+    /// Whether the closing boundary was received.
+    pub complete: bool,
+    pub parts: Vec<PartInfo>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Part<'a> {
+    headers: &'a [u8],
+    data: &'a [u8],
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Splits a `multipart/*` body into its parts.
+///
+/// Returns the parts along with whether the closing boundary was found, or `None` if the
+/// boundary can't be found.
+fn split<'a>(body: &'a [u8], boundary: &str) -> Option<(Vec<Part<'a>>, bool)> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let separator = format!("\r\n--{}", boundary).into_bytes();
+
+    // Anything before the first boundary is a preamble and is ignored.
+    let start = find(body, &delimiter)?;
+    let mut rest = &body[start + delimiter.len()..];
+    let mut parts = Vec::new();
+
+    loop {
+        if rest.starts_with(b"--") {
+            return Some((parts, true));
+        }
+        rest = rest.strip_prefix(b"\r\n")?;
+
+        let end = match find(rest, &separator) {
+            Some(end) => end,
+            None => return Some((parts, false)),
+        };
+        let part = &rest[..end];
+        let (headers, data) = match find(part, b"\r\n\r\n") {
+            Some(idx) => (&part[..idx], &part[idx + 4..]),
+            None => (part, &part[part.len()..]),
+        };
+        parts.push(Part { headers, data });
+
+        rest = &rest[end + separator.len()..];
+    }
+}
+
+/// Value of a header of the part, matched case-insensitively.
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.split("\r\n").find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Value of a `Content-Disposition` parameter, with its quotes removed.
+fn disposition_param(disposition: &str, name: &str) -> Option<String> {
+    disposition.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| {
+            let value = value.trim();
+            value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value)
+                .to_string()
+        })
+    })
+}
+
+fn describe(part: &Part) -> PartInfo {
+    let headers = String::from_utf8_lossy(part.headers);
+    let disposition = header(&headers, "content-disposition");
+
+    PartInfo {
+        name: disposition.and_then(|d| disposition_param(d, "name")),
+        filename: disposition.and_then(|d| disposition_param(d, "filename")),
+        content_type: header(&headers, "content-type").map(str::to_string),
+        size: part.data.len(),
+        content: (part.data.len() <= SMALL_PART_BYTES).then(|| b64engine.encode(part.data)),
+    }
+}
+
+pub fn parse(body: &[u8], boundary: &str) -> Option<MultipartInfo> {
+    let (parts, complete) = split(body, boundary)?;
+
+    Some(MultipartInfo {
+        part_count: parts.len(),
+        total_bytes: parts.iter().map(|p| p.data.len()).sum(),
+        complete,
+        parts: parts.iter().map(describe).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"preamble\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"field\"\r\n\
+        \r\n\
+        value\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+        Content-Type: text/plain\r\n\
+        \r\n\
+        hello\r\nworld\r\n\
+        --XyZ--\r\n";
+
+    #[test]
+    fn test_split() {
+        let (parts, complete) = split(BODY, "XyZ").unwrap();
+
+        assert!(complete);
+        assert_eq!(
+            parts,
+            vec![
+                Part {
+                    headers: b"Content-Disposition: form-data; name=\"field\"",
+                    data: b"value",
+                },
+                Part {
+                    headers: b"Content-Disposition: form-data; name=\"file\"; \
+                        filename=\"a.txt\"\r\nContent-Type: text/plain",
+                    data: b"hello\r\nworld",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_incomplete_body() {
+        let body = &BODY[..BODY.len() - 9];
+
+        assert_eq!(
+            parse(body, "XyZ"),
+            Some(MultipartInfo {
+                part_count: 1,
+                total_bytes: 5,
+                complete: false,
+                parts: vec![PartInfo {
+                    name: Some("field".to_string()),
+                    filename: None,
+                    content_type: None,
+                    size: 5,
+                    content: Some(b64engine.encode("value")),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_omits_content_of_large_parts() {
+        let data = "x".repeat(SMALL_PART_BYTES + 1);
+        let body = format!(
+            "--XyZ\r\ncontent-disposition: form-data; name=big\r\n\r\n{}\r\n--XyZ--",
+            data
+        );
+
+        let info = parse(body.as_bytes(), "XyZ").unwrap();
+
+        assert_eq!(info.parts[0].name.as_deref(), Some("big"));
+        assert_eq!(info.parts[0].size, SMALL_PART_BYTES + 1);
+        assert_eq!(info.parts[0].content, None);
+    }
+
+    #[test]
+    fn test_parse_wrong_boundary() {
+        assert_eq!(parse(BODY, "nope"), None);
+    }
+}
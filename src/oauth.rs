@@ -0,0 +1,170 @@
+use actix_web::{
+    http::header::{CacheControl, CacheDirective, PRAGMA},
+    web, HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+
+pub const OAUTH_TOKEN_PATH: &str = "/__oauth/token";
+
+/// Canned response of the `/__oauth/token` endpoint.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OAuthToken {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    pub refresh_token: Option<String>,
+    /// Grant types a token is issued for, any other one gets an `unsupported_grant_type` error.
+    pub grant_types: Vec<String>,
+}
+
+impl Default for OAuthToken {
+    fn default() -> Self {
+        Self {
+            access_token: "catchall-access-token".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: 3600,
+            refresh_token: None,
+            grant_types: [
+                "authorization_code",
+                "client_credentials",
+                "password",
+                "refresh_token",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    grant_type: Option<String>,
+    scope: Option<String>,
+}
+
+/// Successful access token response (RFC 6749, section 5.1).
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Error response (RFC 6749, section 5.2).
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TokenError {
+    pub error: String,
+    pub error_description: String,
+}
+
+impl TokenError {
+    fn new(error: &str, error_description: String) -> Self {
+        Self {
+            error: error.to_string(),
+            error_description,
+        }
+    }
+}
+
+pub fn issue(settings: &OAuthToken, request: &TokenRequest) -> Result<TokenResponse, TokenError> {
+    let Some(grant_type) = request.grant_type.as_deref() else {
+        return Err(TokenError::new(
+            "invalid_request",
+            "missing grant_type".to_string(),
+        ));
+    };
+    if !settings.grant_types.iter().any(|g| g == grant_type) {
+        return Err(TokenError::new(
+            "unsupported_grant_type",
+            format!("grant type {:?} is not supported", grant_type),
+        ));
+    }
+
+    Ok(TokenResponse {
+        access_token: settings.access_token.clone(),
+        token_type: settings.token_type.clone(),
+        expires_in: settings.expires_in,
+        refresh_token: settings.refresh_token.clone(),
+        scope: request.scope.clone(),
+    })
+}
+
+pub async fn token(
+    form: Option<web::Form<TokenRequest>>,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    let request = form.map_or(
+        TokenRequest {
+            grant_type: None,
+            scope: None,
+        },
+        web::Form::into_inner,
+    );
+
+    let no_store = CacheControl(vec![CacheDirective::NoStore]);
+
+    match issue(&state.settings.oauth_token, &request) {
+        Ok(token) => HttpResponse::Ok()
+            .insert_header(no_store)
+            .insert_header((PRAGMA, "no-cache"))
+            .json(token),
+        Err(error) => HttpResponse::BadRequest()
+            .insert_header(no_store)
+            .insert_header((PRAGMA, "no-cache"))
+            .json(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(grant_type: Option<&str>) -> TokenRequest {
+        TokenRequest {
+            grant_type: grant_type.map(String::from),
+            scope: Some("read".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_issue() {
+        let settings = OAuthToken {
+            refresh_token: Some("refresh".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            issue(&settings, &request(Some("client_credentials"))),
+            Ok(TokenResponse {
+                access_token: "catchall-access-token".to_string(),
+                token_type: "Bearer".to_string(),
+                expires_in: 3600,
+                refresh_token: Some("refresh".to_string()),
+                scope: Some("read".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_issue_rejects_unsupported_grant_types() {
+        let settings = OAuthToken::default();
+
+        assert_eq!(
+            issue(&settings, &request(Some("implicit")))
+                .unwrap_err()
+                .error,
+            "unsupported_grant_type"
+        );
+        assert_eq!(
+            issue(&settings, &request(None)).unwrap_err().error,
+            "invalid_request"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::CatchallResponse;
+
+/// How each request is written to the logs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestLogFormat {
+    /// Pretty-printed JSON echo, through the application logger.
+    #[default]
+    Pretty,
+    /// One OpenTelemetry log record per line on stdout.
+    Otel,
+    /// Both of the above.
+    Both,
+}
+
+impl RequestLogFormat {
+    pub fn pretty(&self) -> bool {
+        matches!(self, RequestLogFormat::Pretty | RequestLogFormat::Both)
+    }
+
+    pub fn otel(&self) -> bool {
+        matches!(self, RequestLogFormat::Otel | RequestLogFormat::Both)
+    }
+}
+
+// SeverityNumber for INFO in the OpenTelemetry log data model.
+const SEVERITY_NUMBER_INFO: u8 = 9;
+
+/// Builds an OpenTelemetry log record for the request, using the HTTP semantic conventions for the
+/// attribute names.
+pub fn log_record(resp: &CatchallResponse, timestamp: DateTime<Utc>) -> Value {
+    let mut attributes = Map::new();
+    attributes.insert("request.id".to_string(), json!(resp.request_id));
+    attributes.insert("http.request.method".to_string(), json!(resp.method));
+    attributes.insert("url.path".to_string(), json!(resp.path));
+    attributes.insert("url.scheme".to_string(), json!(resp.url.scheme));
+    attributes.insert("server.address".to_string(), json!(resp.url.hostname));
+    attributes.insert("server.port".to_string(), json!(resp.url.port));
+    if let Some(ip) = &resp.client.remote_ip {
+        attributes.insert("client.address".to_string(), json!(ip));
+    }
+    if let Some(port) = resp.client.port {
+        attributes.insert("client.port".to_string(), json!(port));
+    }
+    for (name, values) in &resp.headers {
+        attributes.insert(format!("http.request.header.{}", name), json!(values));
+    }
+
+    json!({
+        "timestamp": timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true),
+        "severity": "INFO",
+        "severity_number": SEVERITY_NUMBER_INFO,
+        "body": format!("{} {}", resp.method, resp.path),
+        "attributes": attributes,
+    })
+}
+
+/// Adds the size of the request body and a preview of at most `preview_bytes` bytes of it to a
+/// log record built by [`log_record`].
+pub fn add_body_attributes(record: &mut Value, body: &[u8], preview_bytes: usize) {
+    let Some(attributes) = record["attributes"].as_object_mut() else {
+        return;
+    };
+
+    let preview = &body[..body.len().min(preview_bytes)];
+    attributes.insert("http.request.body.size".to_string(), json!(body.len()));
+    attributes.insert(
+        "http.request.body.preview".to_string(),
+        json!(String::from_utf8_lossy(preview)),
+    );
+    attributes.insert(
+        "http.request.body.truncated".to_string(),
+        json!(preview.len() < body.len()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientInfo, UrlInfo};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_log_record() {
+        let resp = CatchallResponse {
+            request_id: "abc".to_string(),
+            method: "POST".to_string(),
+            path: "/foo".to_string(),
+            client: ClientInfo {
+                remote_ip: Some("10.0.0.1".to_string()),
+                port: Some(12345),
+                ..Default::default()
+            },
+            url: UrlInfo {
+                scheme: "http".to_string(),
+                hostname: "localhost".to_string(),
+                port: 8080,
+                path: "/foo".to_string(),
+                ..Default::default()
+            },
+            headers: HashMap::from([("x-foo".to_string(), vec!["bar".to_string()])]),
+            ..Default::default()
+        };
+        let timestamp = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+
+        assert_eq!(
+            log_record(&resp, timestamp),
+            json!({
+                "timestamp": "2024-05-01T12:30:00.000000000Z",
+                "severity": "INFO",
+                "severity_number": 9,
+                "body": "POST /foo",
+                "attributes": {
+                    "request.id": "abc",
+                    "http.request.method": "POST",
+                    "url.path": "/foo",
+                    "url.scheme": "http",
+                    "server.address": "localhost",
+                    "server.port": 8080,
+                    "client.address": "10.0.0.1",
+                    "client.port": 12345,
+                    "http.request.header.x-foo": ["bar"],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_body_attributes() {
+        let mut record = log_record(&CatchallResponse::default(), Utc::now());
+
+        add_body_attributes(&mut record, b"{\"foo\": \"bar\"}", 8);
+
+        assert_eq!(record["attributes"]["http.request.body.size"], 14);
+        assert_eq!(
+            record["attributes"]["http.request.body.preview"],
+            "{\"foo\": "
+        );
+        assert_eq!(record["attributes"]["http.request.body.truncated"], true);
+
+        let mut record = log_record(&CatchallResponse::default(), Utc::now());
+
+        add_body_attributes(&mut record, b"foo", 8);
+
+        assert_eq!(record["attributes"]["http.request.body.preview"], "foo");
+        assert_eq!(record["attributes"]["http.request.body.truncated"], false);
+    }
+
+    #[test]
+    fn test_log_record_without_client_ip() {
+        let record = log_record(&CatchallResponse::default(), Utc::now());
+
+        assert!(record["attributes"].get("client.address").is_none());
+    }
+}
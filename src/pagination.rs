@@ -0,0 +1,105 @@
+use crate::help::SpecialParam;
+
+pub const PAGINATE_PARAM: &str = "__paginate";
+
+pub const HELP: &[SpecialParam] = &[
+    SpecialParam {
+        name: PAGINATE_PARAM,
+        value: "page=<n>,total=<n>",
+        description: "Adds RFC 8288 `Link` headers (`first`, `prev`, `next`, `last`) pointing back at the request path",
+    },
+];
+
+#[derive(Debug, PartialEq)]
+struct Pagination {
+    page: u32,
+    total: u32,
+}
+
+/// Parses a `page=2,total=10` spec. `page` defaults to 1 and must be within `1..=total`.
+fn parse_spec(spec: &str) -> Option<Pagination> {
+    let mut page = 1;
+    let mut total = None;
+
+    for part in spec.split(',') {
+        let (key, value) = part.split_once('=')?;
+        let value = value.trim().parse::<u32>().ok()?;
+        match key.trim() {
+            "page" => page = value,
+            "total" => total = Some(value),
+            _ => return None,
+        }
+    }
+
+    let total = total?;
+    if page == 0 || page > total {
+        return None;
+    }
+
+    Some(Pagination { page, total })
+}
+
+/// Builds an RFC 8288 `Link` header value for the given pagination spec.
+///
+/// Links point back at `path` and carry an updated `__paginate` parameter so that following them
+/// keeps paginating. `prev` and `next` are omitted on the first and last pages respectively.
+pub fn link_header(path: &str, spec: &str) -> Option<String> {
+    let Pagination { page, total } = parse_spec(spec)?;
+
+    let link = |page: u32, rel: &str| {
+        format!(
+            "<{}?{}=page={},total={}>; rel=\"{}\"",
+            path, PAGINATE_PARAM, page, total, rel
+        )
+    };
+
+    let mut links = vec![link(1, "first")];
+    if page > 1 {
+        links.push(link(page - 1, "prev"));
+    }
+    if page < total {
+        links.push(link(page + 1, "next"));
+    }
+    links.push(link(total, "last"));
+
+    Some(links.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("page=2,total=10", Some(Pagination { page: 2, total: 10 }))]
+    #[case("total=10", Some(Pagination { page: 1, total: 10 }))]
+    #[case("total=10, page=3", Some(Pagination { page: 3, total: 10 }))]
+    #[case("page=11,total=10", None)]
+    #[case("page=0,total=10", None)]
+    #[case("page=2", None)]
+    #[case("page=a,total=10", None)]
+    #[case("foo=1,total=10", None)]
+    fn test_parse_spec(#[case] spec: &str, #[case] expected: Option<Pagination>) {
+        assert_eq!(parse_spec(spec), expected);
+    }
+
+    #[test]
+    fn test_link_header_omits_prev_on_first_page() {
+        let link = link_header("/items", "page=1,total=2").unwrap();
+
+        assert_eq!(
+            link,
+            "</items?__paginate=page=1,total=2>; rel=\"first\", \
+             </items?__paginate=page=2,total=2>; rel=\"next\", \
+             </items?__paginate=page=2,total=2>; rel=\"last\""
+        );
+    }
+
+    #[test]
+    fn test_link_header_omits_next_on_last_page() {
+        let link = link_header("/items", "page=2,total=2").unwrap();
+
+        assert!(link.contains("rel=\"prev\""));
+        assert!(!link.contains("rel=\"next\""));
+    }
+}
@@ -0,0 +1,108 @@
+use serde::Deserialize;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Value configured for a path, e.g. `{ path = "/legacy", value = "HTTP/2.0" }`, or `/legacy=HTTP/2.0`
+/// in a comma-separated environment variable.
+///
+/// Paths are kept in a list rather than as the keys of a table, since the configuration keys are
+/// lowercased and can't hold a `/` when set from the environment.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(
+    try_from = "PathValueRepr<V>",
+    bound = "V: Deserialize<'de> + FromStr, V::Err: Display"
+)]
+pub struct PathValue<V> {
+    pub path: String,
+    pub value: V,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PathValueRepr<V> {
+    Entry { path: String, value: V },
+    Pair(String),
+}
+
+impl<V: FromStr> TryFrom<PathValueRepr<V>> for PathValue<V>
+where
+    V::Err: Display,
+{
+    type Error = String;
+
+    fn try_from(repr: PathValueRepr<V>) -> Result<Self, Self::Error> {
+        match repr {
+            PathValueRepr::Entry { path, value } => Ok(Self { path, value }),
+            PathValueRepr::Pair(pair) => {
+                let (path, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected <path>=<value>, got {}", pair))?;
+                let value = value
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("invalid value for {}: {}", path, e))?;
+
+                Ok(Self::new(path.trim(), value))
+            }
+        }
+    }
+}
+
+impl<V> PathValue<V> {
+    pub fn new(path: &str, value: V) -> Self {
+        Self {
+            path: path.to_string(),
+            value,
+        }
+    }
+}
+
+/// Value configured for `path`, matched exactly.
+pub fn find<'a, V>(values: &'a [PathValue<V>], path: &str) -> Option<&'a V> {
+    values
+        .iter()
+        .find(|value| value.path == path)
+        .map(|value| &value.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_entry() {
+        let value: PathValue<f64> =
+            serde_json::from_value(json!({"path": "/Flaky/*", "value": 0.5})).unwrap();
+
+        assert_eq!(value, PathValue::new("/Flaky/*", 0.5));
+    }
+
+    #[test]
+    fn test_deserialize_pair() {
+        let value: PathValue<String> =
+            serde_json::from_value(json!("/Legal/Doc = https://authority.example/?id=1")).unwrap();
+
+        assert_eq!(
+            value,
+            PathValue::new("/Legal/Doc", "https://authority.example/?id=1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_rejects_invalid_pairs() {
+        assert!(serde_json::from_value::<PathValue<String>>(json!("/legal")).is_err());
+        assert!(serde_json::from_value::<PathValue<f64>>(json!("/flaky=often")).is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let values = vec![
+            PathValue::new("/Legal/Doc", "a"),
+            PathValue::new("/legal", "b"),
+        ];
+
+        assert_eq!(find(&values, "/Legal/Doc"), Some(&"a"));
+        assert_eq!(find(&values, "/legal/doc"), None);
+    }
+}
@@ -0,0 +1,72 @@
+use actix_web::http::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+pub const PAYMENT_TOKEN_HEADER: &str = "x-payment-token";
+
+/// Answers the requests to paid paths without a payment token with a `402`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Paywall {
+    /// Paths requiring a payment, matched exactly.
+    pub paths: Vec<String>,
+    /// Value of the `X-Payment-Token` header letting requests through.
+    pub token: String,
+    /// Price of the paid paths, as a decimal number, e.g. `4.99`.
+    pub amount: String,
+    pub currency: String,
+    /// Where clients pay to get a token.
+    pub payment_url: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRequired {
+    pub error: String,
+    pub amount: String,
+    pub currency: String,
+    pub payment_url: String,
+}
+
+impl Paywall {
+    pub fn requires_payment(&self, headers: &HeaderMap, path: &str) -> bool {
+        self.paths.iter().any(|p| p == path)
+            && headers
+                .get(PAYMENT_TOKEN_HEADER)
+                .is_none_or(|token| token != self.token.as_str())
+    }
+
+    pub fn payment_required(&self) -> PaymentRequired {
+        PaymentRequired {
+            error: "payment required".to_string(),
+            amount: self.amount.clone(),
+            currency: self.currency.clone(),
+            payment_url: self.payment_url.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    #[test]
+    fn test_requires_payment() {
+        let paywall = Paywall {
+            paths: vec!["/premium".to_string()],
+            token: "paid".to_string(),
+            amount: "4.99".to_string(),
+            currency: "EUR".to_string(),
+            payment_url: "https://pay.example".to_string(),
+        };
+        let mut headers = HeaderMap::new();
+
+        assert!(paywall.requires_payment(&headers, "/premium"));
+        assert!(!paywall.requires_payment(&headers, "/premium/free"));
+
+        let header = HeaderName::from_static(PAYMENT_TOKEN_HEADER);
+        headers.insert(header.clone(), HeaderValue::from_static("unpaid"));
+        assert!(paywall.requires_payment(&headers, "/premium"));
+
+        headers.insert(header, HeaderValue::from_static("paid"));
+        assert!(!paywall.requires_payment(&headers, "/premium"));
+    }
+}
@@ -0,0 +1,178 @@
+use prost_reflect::prost::Message;
+use prost_reflect::{
+    DescriptorError, DescriptorPool, DeserializeOptions, DynamicMessage, MessageDescriptor,
+};
+use serde::Serialize;
+use std::fmt;
+
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+#[derive(Debug)]
+pub enum ProtobufError {
+    Io(std::io::Error),
+    Descriptor(DescriptorError),
+    UnknownMessage(String),
+    Encode(serde_json::Error),
+}
+
+impl fmt::Display for ProtobufError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtobufError::Io(e) => write!(f, "unable to read descriptor set: {}", e),
+            ProtobufError::Descriptor(e) => write!(f, "invalid descriptor set: {}", e),
+            ProtobufError::UnknownMessage(m) => {
+                write!(f, "message {} not found in descriptor set", m)
+            }
+            ProtobufError::Encode(e) => write!(f, "unable to encode message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProtobufError {}
+
+/// Encodes the echoed request into a message loaded from a descriptor set.
+///
+/// Only the fields declared by the message are populated, everything else is dropped, so the
+/// message can describe any subset of the `CatchallResponse` using the same field names.
+#[derive(Debug)]
+pub struct ProtobufEncoder {
+    message: MessageDescriptor,
+}
+
+impl ProtobufEncoder {
+    pub fn from_file(path: &str, message: &str) -> Result<Self, ProtobufError> {
+        let bytes = std::fs::read(path).map_err(ProtobufError::Io)?;
+        Self::from_bytes(&bytes, message)
+    }
+
+    pub fn from_bytes(bytes: &[u8], message: &str) -> Result<Self, ProtobufError> {
+        let pool = DescriptorPool::decode(bytes).map_err(ProtobufError::Descriptor)?;
+        let message = pool
+            .get_message_by_name(message)
+            .ok_or_else(|| ProtobufError::UnknownMessage(message.to_string()))?;
+
+        Ok(Self { message })
+    }
+
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ProtobufError> {
+        let value = serde_json::to_value(value).map_err(ProtobufError::Encode)?;
+        let options = DeserializeOptions::new().deny_unknown_fields(false);
+        let message =
+            DynamicMessage::deserialize_with_options(self.message.clone(), value, &options)
+                .map_err(ProtobufError::Encode)?;
+
+        Ok(message.encode_to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_app, AppState};
+    use actix_web::{
+        http::header::ACCEPT,
+        test::{init_service, read_body, TestRequest},
+        web,
+    };
+    use prost_reflect::prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    };
+    use prost_reflect::Value;
+
+    fn field(
+        name: &str,
+        number: i32,
+        r#type: Type,
+        type_name: Option<&str>,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(r#type as i32),
+            type_name: type_name.map(|t| t.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn descriptor_set() -> Vec<u8> {
+        let url = DescriptorProto {
+            name: Some("Url".to_string()),
+            field: vec![
+                field("hostname", 1, Type::String, None),
+                field("port", 2, Type::Uint32, None),
+            ],
+            ..Default::default()
+        };
+        let request = DescriptorProto {
+            name: Some("Request".to_string()),
+            field: vec![
+                field("method", 1, Type::String, None),
+                field("path", 2, Type::String, None),
+                field("url", 3, Type::Message, Some(".catchall.Url")),
+            ],
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("catchall.proto".to_string()),
+            package: Some("catchall".to_string()),
+            message_type: vec![url, request],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        };
+
+        FileDescriptorSet { file: vec![file] }.encode_to_vec()
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_unknown_message() {
+        let res = ProtobufEncoder::from_bytes(&descriptor_set(), "catchall.Nope");
+
+        assert!(matches!(res, Err(ProtobufError::UnknownMessage(_))));
+    }
+
+    #[actix_web::test]
+    async fn test_handler_returns_protobuf() {
+        let encoder = ProtobufEncoder::from_bytes(&descriptor_set(), "catchall.Request").unwrap();
+        let state = AppState {
+            protobuf: Some(encoder),
+            ..Default::default()
+        };
+        let app = init_service(create_app(web::Data::new(state))).await;
+
+        let resp = TestRequest::post()
+            .uri("/foo/bar")
+            .insert_header((ACCEPT, PROTOBUF_CONTENT_TYPE))
+            .send_request(&app)
+            .await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            PROTOBUF_CONTENT_TYPE
+        );
+
+        let bytes = read_body(resp).await;
+        let pool = DescriptorPool::decode(descriptor_set().as_slice()).unwrap();
+        let desc = pool.get_message_by_name("catchall.Request").unwrap();
+        let message = DynamicMessage::decode(desc, bytes).unwrap();
+
+        assert_eq!(
+            message.get_field_by_name("method").unwrap().as_str(),
+            Some("POST")
+        );
+        assert_eq!(
+            message.get_field_by_name("path").unwrap().as_str(),
+            Some("/foo/bar")
+        );
+
+        let url = message.get_field_by_name("url").unwrap();
+        let url = url.as_message().unwrap();
+        assert_eq!(
+            url.get_field_by_name("hostname").unwrap().as_ref(),
+            &Value::String("localhost".to_string())
+        );
+        assert_eq!(url.get_field_by_name("port").unwrap().as_u32(), Some(8080));
+    }
+}
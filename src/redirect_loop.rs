@@ -0,0 +1,39 @@
+use crate::help::SpecialParam;
+
+pub const LOOP_PARAM: &str = "__loop";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: LOOP_PARAM,
+    value: "<hops>",
+    description:
+        "Answers `302` back to the same URL with `__loop` decremented, until it reaches `0`",
+}];
+
+/// Builds the `Location` of the next hop of a `__loop` redirect chain: the same path and query
+/// string, with `__loop` set to `remaining`.
+pub fn next_location(path: &str, query_string: &str, remaining: u32) -> String {
+    let query = query_string
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.split_once('=') {
+            Some((LOOP_PARAM, _)) => format!("{}={}", LOOP_PARAM, remaining),
+            _ => p.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{}?{}", path, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_location() {
+        assert_eq!(
+            next_location("/foo", "a=1&__loop=5&b=%20", 4),
+            "/foo?a=1&__loop=4&b=%20"
+        );
+    }
+}
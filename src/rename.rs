@@ -0,0 +1,36 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Renames the top-level fields of the echo, e.g. `method` to `httpMethod`. Fields absent from
+/// `renames` keep their name.
+pub fn rename_fields(value: Value, renames: &HashMap<String, String>) -> Value {
+    match value {
+        Value::Object(fields) if !renames.is_empty() => fields
+            .into_iter()
+            .map(|(name, value)| match renames.get(&name) {
+                Some(renamed) => (renamed.clone(), value),
+                None => (name, value),
+            })
+            .collect(),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rename_fields() {
+        let renames = HashMap::from([
+            ("method".to_string(), "httpMethod".to_string()),
+            ("missing".to_string(), "other".to_string()),
+        ]);
+
+        assert_eq!(
+            rename_fields(json!({"method": "GET", "path": {"method": 1}}), &renames),
+            json!({"httpMethod": "GET", "path": {"method": 1}})
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use actix_web::http::header::{HeaderMap, HeaderName};
+use serde::Deserialize;
+use ulid::Ulid;
+use uuid::Uuid;
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestIdFormat {
+    #[default]
+    UuidV4,
+    /// Time-ordered UUID, sorts by creation time.
+    UuidV7,
+    /// Time-ordered, 26 characters Crockford base32 identifier.
+    Ulid,
+    /// 21 characters URL-safe identifier.
+    Nanoid,
+}
+
+pub fn generate(format: RequestIdFormat) -> String {
+    match format {
+        RequestIdFormat::UuidV4 => Uuid::new_v4().to_string(),
+        RequestIdFormat::UuidV7 => Uuid::now_v7().to_string(),
+        RequestIdFormat::Ulid => Ulid::generate().to_string(),
+        RequestIdFormat::Nanoid => nanoid::nanoid!(),
+    }
+}
+
+/// Id of the request sent by the client in `X-Request-Id`, so that it can be traced across
+/// services, or a new one.
+pub fn from_headers_or_generate(headers: &HeaderMap, format: RequestIdFormat) -> String {
+    headers
+        .get(X_REQUEST_ID)
+        .and_then(|id| id.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map_or_else(|| generate(format), str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(RequestIdFormat::UuidV4, 4)]
+    #[case(RequestIdFormat::UuidV7, 7)]
+    fn test_generate_uuid(#[case] format: RequestIdFormat, #[case] version: usize) {
+        let id = generate(format);
+
+        assert_eq!(id.len(), 36);
+        assert_eq!(Uuid::parse_str(&id).unwrap().get_version_num(), version);
+    }
+
+    #[test]
+    fn test_generate_uuid_v7_is_time_ordered() {
+        let first = generate(RequestIdFormat::UuidV7);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = generate(RequestIdFormat::UuidV7);
+
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_generate_ulid() {
+        let id = generate(RequestIdFormat::Ulid);
+
+        assert_eq!(id.len(), 26);
+        assert_eq!(Ulid::from_string(&id).unwrap().to_string(), id);
+    }
+
+    #[test]
+    fn test_generate_nanoid() {
+        let id = generate(RequestIdFormat::Nanoid);
+
+        assert_eq!(id.len(), 21);
+        assert!(id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'));
+    }
+
+    #[test]
+    fn test_from_headers_reuses_incoming_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_REQUEST_ID, HeaderValue::from_static(" trace-123 "));
+
+        assert_eq!(
+            from_headers_or_generate(&headers, RequestIdFormat::UuidV4),
+            "trace-123"
+        );
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(""))]
+    fn test_from_headers_generates_missing_id(#[case] incoming: Option<&'static str>) {
+        let mut headers = HeaderMap::new();
+        if let Some(incoming) = incoming {
+            headers.insert(X_REQUEST_ID, HeaderValue::from_static(incoming));
+        }
+
+        let id = from_headers_or_generate(&headers, RequestIdFormat::UuidV4);
+
+        assert_eq!(Uuid::parse_str(&id).unwrap().get_version_num(), 4);
+    }
+}
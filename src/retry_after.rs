@@ -0,0 +1,92 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::{
+        header::{HeaderValue, RETRY_AFTER},
+        StatusCode,
+    },
+    middleware::Next,
+    web, Error,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::AppState;
+
+/// IMF-fixdate, the preferred HTTP-date format (RFC 9110).
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryAfterFormat {
+    /// Number of seconds to wait, e.g. `120`.
+    #[default]
+    Seconds,
+    /// Date after which to retry, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`.
+    Date,
+}
+
+/// `Retry-After` sent along with every `429` and `503`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct RetryAfter {
+    /// Time the client is asked to wait before retrying.
+    pub seconds: u64,
+    #[serde(default)]
+    pub format: RetryAfterFormat,
+}
+
+impl RetryAfter {
+    pub fn value(&self, now: DateTime<Utc>) -> String {
+        match self.format {
+            RetryAfterFormat::Seconds => self.seconds.to_string(),
+            RetryAfterFormat::Date => (now + chrono::Duration::seconds(self.seconds as i64))
+                .format(HTTP_DATE_FORMAT)
+                .to_string(),
+        }
+    }
+}
+
+/// Adds the configured `Retry-After` to `429` and `503` responses that don't have one already.
+pub async fn add_retry_after(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+
+    let mut res = next.call(req).await?;
+
+    let retry_after = state.as_ref().and_then(|s| s.settings.retry_after.as_ref());
+    let throttled = matches!(
+        res.status(),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    );
+    if let Some(retry_after) = retry_after.filter(|_| throttled) {
+        if !res.headers().contains_key(RETRY_AFTER) {
+            let value = HeaderValue::try_from(retry_after.value(Utc::now()))
+                .expect("seconds and dates are valid header values");
+            res.headers_mut().insert(RETRY_AFTER, value);
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(RetryAfterFormat::Seconds, "120")]
+    #[case(RetryAfterFormat::Date, "Wed, 01 May 2024 12:02:00 GMT")]
+    fn test_value(#[case] format: RetryAfterFormat, #[case] expected: &str) {
+        let retry_after = RetryAfter {
+            seconds: 120,
+            format,
+        };
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(retry_after.value(now), expected);
+    }
+}
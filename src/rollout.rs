@@ -0,0 +1,73 @@
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Versions reported in `server.version`, emulating a rolling deployment.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct RollingVersions {
+    /// Versions cycled through, in order.
+    pub versions: Vec<String>,
+    /// Number of requests answered with each version before moving to the next one.
+    pub requests_per_version: u64,
+}
+
+#[derive(Debug)]
+pub struct VersionRoller {
+    versions: Vec<String>,
+    requests_per_version: u64,
+    requests: AtomicU64,
+}
+
+impl VersionRoller {
+    pub fn new(settings: &RollingVersions) -> Result<Self, String> {
+        if settings.versions.is_empty() {
+            return Err("rolling_versions.versions must not be empty".to_string());
+        }
+        if settings.requests_per_version == 0 {
+            return Err("rolling_versions.requests_per_version must be positive".to_string());
+        }
+
+        Ok(Self {
+            versions: settings.versions.clone(),
+            requests_per_version: settings.requests_per_version,
+            requests: AtomicU64::new(0),
+        })
+    }
+
+    /// Counts a request and returns the version it's answered with.
+    pub fn next(&self) -> &str {
+        let request = self.requests.fetch_add(1, Ordering::Relaxed);
+        let index = (request / self.requests_per_version) % self.versions.len() as u64;
+
+        &self.versions[index as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(versions: &[&str], requests_per_version: u64) -> RollingVersions {
+        RollingVersions {
+            versions: versions.iter().map(|v| v.to_string()).collect(),
+            requests_per_version,
+        }
+    }
+
+    #[test]
+    fn test_next_cycles_through_versions() {
+        let roller = VersionRoller::new(&settings(&["1.0", "1.1", "2.0"], 2)).unwrap();
+
+        let versions: Vec<_> = (0..8).map(|_| roller.next().to_string()).collect();
+
+        assert_eq!(
+            versions,
+            vec!["1.0", "1.0", "1.1", "1.1", "2.0", "2.0", "1.0", "1.0"]
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_settings() {
+        assert!(VersionRoller::new(&settings(&[], 2)).is_err());
+        assert!(VersionRoller::new(&settings(&["1.0"], 0)).is_err());
+    }
+}
@@ -0,0 +1,520 @@
+use config::{Config, ConfigError};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::batch::Batching;
+use crate::captive_portal::CaptivePortal;
+use crate::dedup::DedupCache;
+use crate::degradation::HealthWindow;
+use crate::dependencies::DependencyChain;
+use crate::fuzz::FuzzHeaders;
+use crate::idempotency::IdempotencyTracking;
+use crate::latency::LatencyProfile;
+use crate::logging::LogFormat;
+use crate::oauth::OAuthToken;
+use crate::otel::RequestLogFormat;
+use crate::path_value::PathValue;
+use crate::paywall::Paywall;
+use crate::request_id::RequestIdFormat;
+use crate::retry_after::RetryAfter;
+use crate::rollout::RollingVersions;
+use crate::signing::SigningAlgorithm;
+
+/// Name of the optional configuration file, without extension, looked up in the working directory.
+const CONFIG_FILE: &str = "catchall";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AppSettings {
+    pub host: String,
+    pub port: u16,
+    pub workers: usize,
+    /// Path of a unix socket to listen on, instead of `host` and `port`.
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// Time given to in-flight requests to complete on shutdown, before their connections are closed.
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// Origins allowed to make cross-origin requests, `*` for any. CORS is disabled when empty.
+    #[serde(default = "default_cors_allowed_origins")]
+    pub cors_allowed_origins: Vec<String>,
+    /// Path to the PEM certificate chain served over HTTPS, along with `tls_key_path`.
+    #[serde(default)]
+    pub tls_cert_path: Option<String>,
+    /// Path to the PEM private key of `tls_cert_path`.
+    #[serde(default)]
+    pub tls_key_path: Option<String>,
+    /// Path to a compiled protobuf descriptor set (`protoc --descriptor_set_out`).
+    #[serde(default)]
+    pub protobuf_descriptor_path: Option<String>,
+    /// Fully qualified name of the message the request is encoded into.
+    #[serde(default)]
+    pub protobuf_message: Option<String>,
+    /// Answer requests carrying an `Expect` header with `417 Expectation Failed`.
+    #[serde(default)]
+    pub reject_expectations: bool,
+    /// Answer `400 Bad Request` when a body declared as JSON fails to parse.
+    #[serde(default)]
+    pub strict_json: bool,
+    /// Delay between the chunks of a `__split=chunks` response.
+    #[serde(default = "default_split_delay_ms")]
+    pub split_delay_ms: u64,
+    /// Maximum delay requested with `__delay_ms` or scaled with `delay_per_kb_ms`, longer ones are
+    /// capped.
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Delay per KiB of request body before responding, capped at `max_delay_ms`.
+    #[serde(default)]
+    pub delay_per_kb_ms: Option<u64>,
+    /// Path to a MaxMind GeoIP2/GeoLite2 City database used to enrich the client IP.
+    #[serde(default)]
+    pub geoip_db_path: Option<String>,
+    /// Path to a MaxMind GeoLite2 ASN database, only used along with `geoip_db_path`.
+    #[serde(default)]
+    pub geoip_asn_db_path: Option<String>,
+    /// Secret used to sign response bodies with an HMAC in the `X-Signature` header.
+    #[serde(default)]
+    pub response_signing_secret: Option<String>,
+    #[serde(default)]
+    pub response_signing_algorithm: SigningAlgorithm,
+    /// Base64 of an Ed25519 private key signing response bodies, along with the HMAC signature.
+    #[serde(default)]
+    pub response_signing_ed25519_key: Option<String>,
+    /// Network condition profiles selectable with `__profile=<name>`.
+    #[serde(default)]
+    pub latency_profiles: HashMap<String, LatencyProfile>,
+    /// Maximum number of simultaneous requests per client IP, unlimited when unset.
+    #[serde(default)]
+    pub max_connections_per_ip: Option<usize>,
+    /// Format of the identifier generated for each request.
+    #[serde(default)]
+    pub request_id_format: RequestIdFormat,
+    /// Parse `multipart/*` bodies and report their part count and size.
+    #[serde(default = "default_true")]
+    pub parse_multipart: bool,
+    /// Format of the application logs.
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// How each request is written to the logs.
+    #[serde(default)]
+    pub request_log_format: RequestLogFormat,
+    /// Reflect each cookie with its raw and decoded value in `cookie_details`.
+    #[serde(default)]
+    pub cookie_details: bool,
+    /// Realm announced in the challenges returned by `__challenge`.
+    #[serde(default = "default_challenge_realm")]
+    pub challenge_realm: String,
+    /// Maximum number of distinct paths tracked in `/__stats/paths`.
+    #[serde(default = "default_stats_max_paths")]
+    pub stats_max_paths: usize,
+    /// Gzip the body before base64-encoding it in `body.raw`.
+    #[serde(default)]
+    pub compress_raw_body: bool,
+    /// Size of the body echoed in `body.raw`, larger ones are truncated.
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Parse `application/toml` and `text/toml` bodies into `body.toml`.
+    #[serde(default = "default_true")]
+    pub parse_toml: bool,
+    /// Parse `text/ini` bodies, or untyped ones starting with a `[section]`, into `body.ini`.
+    #[serde(default = "default_true")]
+    pub parse_ini: bool,
+    /// Random headers injected in every response to stress the clients' header parsers.
+    #[serde(default)]
+    pub fuzz_headers: Option<FuzzHeaders>,
+    /// Maximum time spent producing a response, injected delays included, before answering `504`.
+    #[serde(default)]
+    pub response_timeout_ms: Option<u64>,
+    /// Probability, between 0 and 1, of answering any request with a `500`.
+    #[serde(default)]
+    pub error_rate: f64,
+    /// Error rates overriding `error_rate` by path prefix, e.g. `/flaky/*`. The longest matching
+    /// prefix wins.
+    #[serde(default)]
+    pub path_error_rates: Vec<PathValue<f64>>,
+    /// Seed of the generator deciding which requests fail, random when unset.
+    #[serde(default)]
+    pub error_seed: Option<u64>,
+    /// Render the echo as syntax-highlighted HTML for clients accepting `text/html`.
+    #[serde(default)]
+    pub html_highlight: bool,
+    /// Versions reported in `server.version`, changing every few requests.
+    #[serde(default)]
+    pub rolling_versions: Option<RollingVersions>,
+    /// Query parameters every request must include, or be answered with a `400`.
+    #[serde(default)]
+    pub required_query_params: Vec<String>,
+    /// Add the size and a preview of the request body to the OpenTelemetry log records.
+    #[serde(default)]
+    pub trace_body_attributes: bool,
+    /// Maximum number of bytes of the request body included in the OpenTelemetry log records.
+    #[serde(default = "default_trace_body_preview_bytes")]
+    pub trace_body_preview_bytes: usize,
+    /// Maximum request body size, in bytes, by content type, e.g. `application/json` or
+    /// `multipart/*`, matched case-insensitively. Other content types are limited to 256 KiB.
+    #[serde(default)]
+    pub content_type_limits: HashMap<String, usize>,
+    /// Paths answered with a `451`, along with the URL of the authority requesting the block.
+    #[serde(default)]
+    pub blocked_paths: Vec<PathValue<String>>,
+    /// Paths answered with a `426`, along with the protocol to upgrade to.
+    #[serde(default)]
+    pub upgrade_required_paths: Vec<PathValue<String>>,
+    /// Processing time budget reported against the actual time in `slo`.
+    #[serde(default)]
+    pub slo_budget_ms: Option<u64>,
+    /// `Retry-After` sent along with every `429` and `503`.
+    #[serde(default)]
+    pub retry_after: Option<RetryAfter>,
+    /// Answer the requests past the first few of every window with a `503`.
+    #[serde(default)]
+    pub health_window: Option<HealthWindow>,
+    /// Decode the JWT of an `Authorization: Bearer` header into `jwt`, without checking it.
+    #[serde(default)]
+    pub decode_jwt: bool,
+    /// Expected JSON body by path, the request body is diffed against in `diff`. Baselines are
+    /// JSON documents given as strings, since the configuration keys are lowercased.
+    #[serde(default)]
+    pub body_baselines: HashMap<String, String>,
+    /// Maximum nesting depth of JSON bodies, deeper ones are answered with a `400`.
+    #[serde(default)]
+    pub max_json_depth: Option<usize>,
+    /// Answer identical requests (same method, path and body) with the echo of the first one.
+    #[serde(default)]
+    pub dedup_cache: Option<DedupCache>,
+    /// Delay before a JSON body posted to a path is returned to `GET` requests on it.
+    #[serde(default)]
+    pub consistency_delay_ms: Option<u64>,
+    /// Number of paths whose writes are kept for `consistency_delay_ms`, the oldest being dropped.
+    #[serde(default = "default_consistency_max_paths")]
+    pub consistency_max_paths: usize,
+    /// Collect echoes into batches, returned as a whole to the request closing them.
+    #[serde(default)]
+    pub batching: Option<Batching>,
+    /// Report in `idempotency` when a structurally identical request was first seen.
+    #[serde(default)]
+    pub idempotency_tracking: Option<IdempotencyTracking>,
+    /// jq filter producing the response body from the JSON body of the request.
+    #[serde(default)]
+    pub jq_transform: Option<String>,
+    /// Body returned to every request instead of its echo.
+    #[serde(default)]
+    pub static_response_body: Option<String>,
+    /// File whose content is returned to every request instead of its echo.
+    #[serde(default)]
+    pub static_response_file: Option<String>,
+    /// Content type of the static response.
+    #[serde(default = "default_static_response_content_type")]
+    pub static_response_content_type: String,
+    /// Canned response of the `/__oauth/token` endpoint.
+    #[serde(default)]
+    pub oauth_token: OAuthToken,
+    /// New names of the top-level fields of the JSON echo, e.g. `method = "httpMethod"`.
+    #[serde(default)]
+    pub field_renames: HashMap<String, String>,
+    /// Answer the requests without a session cookie with a `511` linking to a login page.
+    #[serde(default)]
+    pub captive_portal: Option<CaptivePortal>,
+    /// Answer the requests to paid paths without a payment token with a `402`.
+    #[serde(default)]
+    pub paywall: Option<Paywall>,
+    /// Downstream dependencies every request is delayed by, reported in `dependencies`.
+    #[serde(default)]
+    pub dependency_chain: Option<DependencyChain>,
+    /// Replace the volatile fields of every echo with placeholders, as `__stable=1` does.
+    #[serde(default)]
+    pub stable_snapshots: bool,
+    /// Path of the health check endpoint, whose requests aren't echoed nor logged.
+    #[serde(default = "default_health_path")]
+    pub health_path: String,
+    /// Number of echoes kept for `/__history`, `0` to keep none.
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    /// Explain in `negotiation` how the format of the response was picked.
+    #[serde(default)]
+    pub negotiation_trace: bool,
+    /// Mirror the request headers prefixed with `X-Echo-` as response headers, without the prefix.
+    #[serde(default)]
+    pub mirror_echo_headers: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_challenge_realm() -> String {
+    "catchall".to_string()
+}
+
+fn default_stats_max_paths() -> usize {
+    1000
+}
+
+fn default_consistency_max_paths() -> usize {
+    1000
+}
+
+fn default_split_delay_ms() -> u64 {
+    100
+}
+
+fn default_health_path() -> String {
+    "/__health".to_string()
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_cors_allowed_origins() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+fn default_max_body_bytes() -> usize {
+    10 * 1024 * 1024
+}
+
+fn default_static_response_content_type() -> String {
+    "application/json".to_string()
+}
+
+fn default_history_size() -> usize {
+    100
+}
+
+fn default_max_delay_ms() -> u64 {
+    30000
+}
+
+fn default_trace_body_preview_bytes() -> usize {
+    256
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            workers: 2,
+            unix_socket: None,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            cors_allowed_origins: default_cors_allowed_origins(),
+            tls_cert_path: None,
+            tls_key_path: None,
+            protobuf_descriptor_path: None,
+            protobuf_message: None,
+            reject_expectations: false,
+            strict_json: false,
+            split_delay_ms: default_split_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+            delay_per_kb_ms: None,
+            geoip_db_path: None,
+            geoip_asn_db_path: None,
+            response_signing_secret: None,
+            response_signing_algorithm: SigningAlgorithm::default(),
+            response_signing_ed25519_key: None,
+            latency_profiles: HashMap::new(),
+            max_connections_per_ip: None,
+            request_id_format: RequestIdFormat::default(),
+            parse_multipart: true,
+            log_format: LogFormat::default(),
+            request_log_format: RequestLogFormat::default(),
+            cookie_details: false,
+            challenge_realm: default_challenge_realm(),
+            stats_max_paths: default_stats_max_paths(),
+            compress_raw_body: false,
+            max_body_bytes: default_max_body_bytes(),
+            parse_toml: true,
+            parse_ini: true,
+            fuzz_headers: None,
+            response_timeout_ms: None,
+            error_rate: 0.0,
+            path_error_rates: Vec::new(),
+            error_seed: None,
+            html_highlight: false,
+            rolling_versions: None,
+            required_query_params: Vec::new(),
+            trace_body_attributes: false,
+            trace_body_preview_bytes: default_trace_body_preview_bytes(),
+            content_type_limits: HashMap::new(),
+            blocked_paths: Vec::new(),
+            upgrade_required_paths: Vec::new(),
+            slo_budget_ms: None,
+            retry_after: None,
+            health_window: None,
+            decode_jwt: false,
+            body_baselines: HashMap::new(),
+            max_json_depth: None,
+            dedup_cache: None,
+            consistency_delay_ms: None,
+            consistency_max_paths: default_consistency_max_paths(),
+            batching: None,
+            idempotency_tracking: None,
+            jq_transform: None,
+            static_response_body: None,
+            static_response_file: None,
+            static_response_content_type: default_static_response_content_type(),
+            oauth_token: OAuthToken::default(),
+            field_renames: HashMap::new(),
+            captive_portal: None,
+            paywall: None,
+            dependency_chain: None,
+            stable_snapshots: false,
+            health_path: default_health_path(),
+            history_size: default_history_size(),
+            negotiation_trace: false,
+            mirror_echo_headers: false,
+        }
+    }
+}
+
+fn env_source() -> config::Environment {
+    config::Environment::with_prefix("CATCHALL_API")
+        .prefix_separator("_")
+        .separator("__")
+        .try_parsing(true)
+        .list_separator(",")
+        .with_list_parse_key("required_query_params")
+        .with_list_parse_key("cors_allowed_origins")
+        .with_list_parse_key("path_error_rates")
+        .with_list_parse_key("blocked_paths")
+        .with_list_parse_key("upgrade_required_paths")
+}
+
+/// Loads the configuration from an optional `catchall.{toml,yaml,json,...}` file, or the file
+/// pointed to by `CATCHALL_API_CONFIG_FILE`, overridden by `CATCHALL_API_*` environment variables.
+///
+/// Nested values can be set from the environment by separating keys with `__`, e.g.
+/// `CATCHALL_API_LATENCY_PROFILES__MOBILE3G__DELAY_MS=300`.
+pub fn get_config() -> Result<Config, ConfigError> {
+    let file_source = match std::env::var("CATCHALL_API_CONFIG_FILE") {
+        Ok(path) => config::File::with_name(&path),
+        Err(_) => config::File::with_name(CONFIG_FILE).required(false),
+    };
+    Ok(Config::builder()
+        .set_default("host", "0.0.0.0")?
+        .set_default("port", 8080)?
+        .set_default("workers", 2)?
+        .add_source(file_source)
+        .add_source(env_source())
+        .build()
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body_limit::ContentTypeLimits;
+    use crate::path_value;
+    use config::{File, FileFormat};
+
+    fn from_toml(toml: &str) -> AppSettings {
+        Config::builder()
+            .add_source(File::from_str(
+                "host = \"::\"\nport = 80\nworkers = 1",
+                FileFormat::Toml,
+            ))
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    fn from_env(vars: &[(&str, &str)]) -> AppSettings {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Config::builder()
+            .add_source(File::from_str(
+                "host = \"::\"\nport = 80\nworkers = 1",
+                FileFormat::Toml,
+            ))
+            .add_source(env_source().source(Some(vars)))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_path_values_from_env_keep_their_case() {
+        let settings = from_env(&[
+            (
+                "CATCHALL_API_BLOCKED_PATHS",
+                "/Legal/Doc=https://authority.example/notices/42",
+            ),
+            (
+                "CATCHALL_API_UPGRADE_REQUIRED_PATHS",
+                "/Legacy=HTTP/2.0,/tls=TLS/1.3",
+            ),
+            ("CATCHALL_API_PATH_ERROR_RATES", "/Flaky/*=0.5"),
+        ]);
+
+        assert_eq!(
+            path_value::find(&settings.blocked_paths, "/Legal/Doc"),
+            Some(&"https://authority.example/notices/42".to_string())
+        );
+        assert_eq!(
+            path_value::find(&settings.blocked_paths, "/legal/doc"),
+            None
+        );
+        assert_eq!(
+            path_value::find(&settings.upgrade_required_paths, "/Legacy"),
+            Some(&"HTTP/2.0".to_string())
+        );
+        assert_eq!(
+            settings.path_error_rates,
+            vec![PathValue::new("/Flaky/*", 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_path_values_from_file_keep_their_case() {
+        let settings = from_toml(
+            "blocked_paths = [{ path = \"/Legal/Doc\", value = \"https://authority.example\" }]",
+        );
+
+        assert_eq!(
+            path_value::find(&settings.blocked_paths, "/Legal/Doc"),
+            Some(&"https://authority.example".to_string())
+        );
+    }
+
+    #[test]
+    fn test_content_type_limits_match_case_insensitively() {
+        let settings = from_toml("[content_type_limits]\n\"Application/JSON\" = 1024");
+        let limits = ContentTypeLimits::new(&settings.content_type_limits);
+
+        assert_eq!(limits.limit("application/json"), 1024);
+        assert_eq!(limits.limit("Application/Json"), 1024);
+    }
+
+    #[test]
+    fn test_cors_allowed_origins() {
+        assert_eq!(from_toml("").cors_allowed_origins, vec!["*"]);
+        assert_eq!(
+            from_toml("cors_allowed_origins = []").cors_allowed_origins,
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_unix_socket() {
+        assert_eq!(from_toml("").unix_socket, None);
+        assert_eq!(
+            from_toml("unix_socket = \"/run/catchall.sock\"").unix_socket,
+            Some("/run/catchall.sock".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shutdown_timeout_secs() {
+        assert_eq!(from_toml("").shutdown_timeout_secs, 30);
+        assert_eq!(
+            from_toml("shutdown_timeout_secs = 5").shutdown_timeout_secs,
+            5
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use base64::{engine::general_purpose::STANDARD as b64engine, Engine as _};
+use ed25519_dalek::{Signer, SigningKey};
+use hmac::{Hmac, KeyInit, Mac};
+use serde::Deserialize;
+use sha2::{Sha256, Sha384, Sha512};
+
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+pub const ED25519_SIGNATURE_HEADER: &str = "X-Signature-Ed25519";
+pub const PUBLIC_KEY_HEADER: &str = "X-Public-Key";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningAlgorithm {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl SigningAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::Sha256 => "sha256",
+            SigningAlgorithm::Sha384 => "sha384",
+            SigningAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+fn hmac<M: Mac + KeyInit>(secret: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut mac = <M as KeyInit>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Signs `body`, returning the header value in the `<algorithm>=<hex digest>` form.
+pub fn sign(algorithm: SigningAlgorithm, secret: &str, body: &[u8]) -> String {
+    let secret = secret.as_bytes();
+    let digest = match algorithm {
+        SigningAlgorithm::Sha256 => hmac::<Hmac<Sha256>>(secret, body),
+        SigningAlgorithm::Sha384 => hmac::<Hmac<Sha384>>(secret, body),
+        SigningAlgorithm::Sha512 => hmac::<Hmac<Sha512>>(secret, body),
+    };
+
+    format!("{}={}", algorithm.name(), hex::encode(digest))
+}
+
+/// Signs response bodies with an Ed25519 key, for clients to check them against its public key.
+#[derive(Debug)]
+pub struct Ed25519Signer {
+    key: SigningKey,
+    /// Base64 of the public key, as sent in `X-Public-Key`.
+    public_key: String,
+}
+
+impl Ed25519Signer {
+    /// Builds the signer from the base64 of a 32 bytes private key.
+    pub fn new(private_key: &str) -> Result<Self, String> {
+        let seed: [u8; 32] = b64engine
+            .decode(private_key.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or("the Ed25519 private key must be 32 bytes, base64-encoded")?;
+        let key = SigningKey::from_bytes(&seed);
+        let public_key = b64engine.encode(key.verifying_key().as_bytes());
+
+        Ok(Self { key, public_key })
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Base64 of the signature of `body`.
+    pub fn sign(&self, body: &[u8]) -> String {
+        b64engine.encode(self.key.sign(body).to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // Reference values from RFC 4231, test case 2.
+    #[rstest]
+    #[case(
+        SigningAlgorithm::Sha256,
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    )]
+    #[case(
+        SigningAlgorithm::Sha384,
+        "sha384=af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e\
+         8e2240ca5e69e2c78b3239ecfab21649"
+    )]
+    #[case(
+        SigningAlgorithm::Sha512,
+        "sha512=164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+         9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+    )]
+    fn test_sign(#[case] algorithm: SigningAlgorithm, #[case] expected: &str) {
+        assert_eq!(
+            sign(algorithm, "Jefe", b"what do ya want for nothing?"),
+            expected
+        );
+    }
+
+    // Test vector from RFC 8032, section 7.1, test 2.
+    #[test]
+    fn test_ed25519_sign() {
+        let private_key =
+            hex::decode("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb")
+                .unwrap();
+        let signer = Ed25519Signer::new(&b64engine.encode(private_key)).unwrap();
+
+        assert_eq!(
+            hex::encode(b64engine.decode(signer.public_key()).unwrap()),
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"
+        );
+        assert_eq!(
+            hex::encode(b64engine.decode(signer.sign(&[0x72])).unwrap()),
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+             085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+        );
+    }
+
+    #[test]
+    fn test_ed25519_rejects_invalid_keys() {
+        assert!(Ed25519Signer::new("not base64!").is_err());
+        assert!(Ed25519Signer::new(&b64engine.encode([0u8; 16])).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use actix_http::{BoxedPayloadStream, Payload};
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    error::PayloadError,
+    middleware::Next,
+    rt::time::sleep,
+    web::{self, Bytes},
+    Error, HttpMessage,
+};
+use futures_util::{stream, Stream, StreamExt};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::help::SpecialParam;
+
+pub const SLOW_READ_PARAM: &str = "__slow_read";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: SLOW_READ_PARAM,
+    value: "<bytes per second>",
+    description: "Reads the request body at the given rate, to simulate backpressure",
+}];
+
+/// Number of slices the body is read in every second, whatever the rate.
+const SLICES_PER_SEC: u64 = 10;
+
+/// Reads the payload at `bytes_per_sec`, in slices of a tenth of a second.
+///
+/// The payload isn't polled while waiting, so once the socket buffers are full the client is held
+/// back by TCP flow control, as it would be by a server that can't keep up.
+pub fn throttle(
+    payload: Payload,
+    bytes_per_sec: u64,
+) -> impl Stream<Item = Result<Bytes, PayloadError>> {
+    let slice_len = (bytes_per_sec / SLICES_PER_SEC).max(1) as usize;
+
+    stream::unfold(
+        (payload, Bytes::new()),
+        move |(mut payload, mut pending)| async move {
+            if pending.is_empty() {
+                match payload.next().await? {
+                    Ok(chunk) => pending = chunk,
+                    Err(e) => return Some((Err(e), (payload, pending))),
+                }
+            }
+
+            let slice = pending.split_to(slice_len.min(pending.len()));
+            sleep(Duration::from_secs_f64(
+                slice.len() as f64 / bytes_per_sec as f64,
+            ))
+            .await;
+
+            Some((Ok(slice), (payload, pending)))
+        },
+    )
+}
+
+/// Throttles the reading of the body of requests with `__slow_read=<bytes per second>`.
+///
+/// actix buffers the whole body before the handler runs, so the throttling happens underneath, as
+/// the body is read off the connection, and the handler still gets the complete body.
+pub async fn slow_read(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let bytes_per_sec = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .ok()
+        .and_then(|query| query.get(SLOW_READ_PARAM)?.parse::<u64>().ok())
+        .filter(|rate| *rate > 0);
+
+    if let Some(bytes_per_sec) = bytes_per_sec {
+        let stream: BoxedPayloadStream = Box::pin(throttle(req.take_payload(), bytes_per_sec));
+        req.set_payload(Payload::from(stream));
+    }
+
+    next.call(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_throttle_reads_in_slices() {
+        let chunks = vec![
+            Ok(Bytes::from_static(b"abcdefghij")),
+            Ok(Bytes::from_static(b"klm")),
+        ];
+        let inner: BoxedPayloadStream = Box::pin(stream::iter(chunks));
+
+        let slices: Vec<Bytes> = throttle(Payload::from(inner), 40)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(
+            slices,
+            vec![
+                Bytes::from_static(b"abcd"),
+                Bytes::from_static(b"efgh"),
+                Bytes::from_static(b"ij"),
+                Bytes::from_static(b"klm"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use actix_web::{rt::time::sleep, web::Bytes, Error};
+use futures_util::{stream, Stream, StreamExt};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+use crate::help::SpecialParam;
+
+pub const SPLIT_PARAM: &str = "__split";
+pub const SPLIT_CHUNKS: &str = "chunks";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: SPLIT_PARAM,
+    value: "chunks",
+    description: "Streams the echo as a JSON array of single-field objects, one chunk per element",
+}];
+
+/// Splits a JSON object into the chunks of a JSON array, with one single-field object per element.
+///
+/// The opening and closing brackets and the separating commas are attached to the elements so
+/// that every chunk carries exactly one element and the concatenation is a valid JSON array.
+pub fn chunks(value: Value) -> Vec<Bytes> {
+    let fields = match value {
+        Value::Object(fields) => fields,
+        other => return vec![Bytes::from(format!("[{}]", other))],
+    };
+
+    if fields.is_empty() {
+        return vec![Bytes::from_static(b"[]")];
+    }
+
+    let last = fields.len() - 1;
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (key, value))| {
+            let element = Value::Object(Map::from_iter([(key, value)]));
+            let prefix = if idx == 0 { "[" } else { "," };
+            let suffix = if idx == last { "]" } else { "" };
+            Bytes::from(format!("{}{}{}", prefix, element, suffix))
+        })
+        .collect()
+}
+
+/// Streams the chunks, waiting `delay` before sending each of them.
+pub fn stream(chunks: Vec<Bytes>, delay: Duration) -> impl Stream<Item = Result<Bytes, Error>> {
+    stream::iter(chunks).then(move |chunk| async move {
+        sleep(delay).await;
+        Ok(chunk)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chunks_holds_one_element_per_chunk() {
+        let chunks = chunks(json!({"method": "GET", "path": "/foo"}));
+
+        assert_eq!(
+            chunks,
+            vec![
+                Bytes::from_static(b"[{\"method\":\"GET\"}"),
+                Bytes::from_static(b",{\"path\":\"/foo\"}]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunks_of_empty_object_is_empty_array() {
+        assert_eq!(chunks(json!({})), vec![Bytes::from_static(b"[]")]);
+    }
+}
@@ -0,0 +1,65 @@
+use serde_json::Value;
+
+use crate::help::SpecialParam;
+
+pub const STABLE_PARAM: &str = "__stable";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: STABLE_PARAM,
+    value: "1",
+    description: "Replaces the volatile fields of the echo with placeholders, for snapshot tests",
+}];
+
+/// Fields varying from one request to the next, by JSON pointer, with their placeholder.
+const VOLATILE_FIELDS: &[(&str, &str)] = &[
+    ("/request_id", "<request_id>"),
+    ("/received_at", "<timestamp>"),
+    ("/client/port", "<port>"),
+    ("/server/version", "<version>"),
+    ("/slo/actual_ms", "<duration>"),
+    ("/slo/within_budget", "<within_budget>"),
+    ("/idempotency/first_seen", "<timestamp>"),
+    ("/idempotency/age_secs", "<duration>"),
+];
+
+pub fn enabled(value: &str) -> bool {
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
+
+/// Replaces the volatile fields of the echo with placeholders. Since objects are serialized with
+/// sorted keys, identical requests then give byte-identical echoes.
+pub fn snapshot(mut echo: Value) -> Value {
+    for (pointer, placeholder) in VOLATILE_FIELDS {
+        if let Some(field) = echo.pointer_mut(pointer).filter(|f| !f.is_null()) {
+            *field = Value::from(*placeholder);
+        }
+    }
+
+    echo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_snapshot() {
+        let echo = json!({
+            "request_id": "01J0000000",
+            "client": {"remote_ip": "127.0.0.1", "port": 51234},
+            "server": null,
+            "path": "/orders",
+        });
+
+        assert_eq!(
+            snapshot(echo),
+            json!({
+                "request_id": "<request_id>",
+                "client": {"remote_ip": "127.0.0.1", "port": "<port>"},
+                "server": null,
+                "path": "/orders",
+            })
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::StatusCode,
+    middleware::Next,
+    web, Error, HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+
+pub const STATS_PATH: &str = "/__stats/paths";
+
+/// Key under which requests are aggregated once the maximum number of paths is tracked.
+pub const OTHER_PATHS: &str = "<other>";
+
+/// Prefix of the endpoints of the API itself, e.g. `/__metrics`.
+const INTERNAL_PATH_PREFIX: &str = "/__";
+
+/// Whether `path` is an endpoint of the API itself or its health check, rather than real traffic.
+pub fn is_internal(path: &str, health_path: &str) -> bool {
+    path.starts_with(INTERNAL_PATH_PREFIX) || path == health_path
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PathStats {
+    pub count: u64,
+    pub avg_latency_ms: f64,
+    pub min_latency_ms: f64,
+    pub max_latency_ms: f64,
+    pub statuses: BTreeMap<u16, u64>,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+    statuses: BTreeMap<u16, u64>,
+}
+
+impl Entry {
+    fn record(&mut self, status: StatusCode, latency: Duration) {
+        self.min = if self.count == 0 {
+            latency
+        } else {
+            self.min.min(latency)
+        };
+        self.max = self.max.max(latency);
+        self.total += latency;
+        self.count += 1;
+        *self.statuses.entry(status.as_u16()).or_insert(0) += 1;
+    }
+
+    fn stats(&self) -> PathStats {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        PathStats {
+            count: self.count,
+            avg_latency_ms: ms(self.total) / self.count as f64,
+            min_latency_ms: ms(self.min),
+            max_latency_ms: ms(self.max),
+            statuses: self.statuses.clone(),
+        }
+    }
+}
+
+/// Request statistics aggregated by path.
+///
+/// At most `max_paths` distinct paths are tracked, requests to any other path are aggregated
+/// under [`OTHER_PATHS`] to keep the memory bounded.
+#[derive(Debug)]
+pub struct PathStatsStore {
+    max_paths: usize,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl PathStatsStore {
+    pub fn new(max_paths: usize) -> Self {
+        Self {
+            max_paths,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, path: &str, status: StatusCode, latency: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let key = if entries.contains_key(path) || entries.len() < self.max_paths {
+            path
+        } else {
+            OTHER_PATHS
+        };
+
+        entries
+            .entry(key.to_string())
+            .or_default()
+            .record(status, latency);
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, PathStats> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(path, entry)| (path.clone(), entry.stats()))
+            .collect()
+    }
+}
+
+pub async fn record_stats(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let start = Instant::now();
+    let path = req.path().to_string();
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+
+    let res = next.call(req).await?;
+
+    if let Some(state) = state {
+        if !is_internal(&path, &state.settings.health_path) {
+            state
+                .path_stats
+                .record(&path, res.status(), start.elapsed());
+        }
+    }
+
+    Ok(res)
+}
+
+pub async fn path_stats(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.path_stats.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("/__stats/paths", true)]
+    #[case("/__oauth/token", true)]
+    #[case("/health", true)]
+    #[case("/healthz", false)]
+    #[case("/orders/__x", false)]
+    fn test_is_internal(#[case] path: &str, #[case] expected: bool) {
+        assert_eq!(is_internal(path, "/health"), expected);
+    }
+
+    #[test]
+    fn test_record() {
+        let store = PathStatsStore::new(10);
+
+        store.record("/a", StatusCode::OK, Duration::from_millis(10));
+        store.record("/a", StatusCode::OK, Duration::from_millis(30));
+        store.record("/a", StatusCode::NOT_FOUND, Duration::from_millis(20));
+
+        assert_eq!(
+            store.snapshot().get("/a"),
+            Some(&PathStats {
+                count: 3,
+                avg_latency_ms: 20.0,
+                min_latency_ms: 10.0,
+                max_latency_ms: 30.0,
+                statuses: BTreeMap::from([(200, 2), (404, 1)]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_caps_paths() {
+        let store = PathStatsStore::new(2);
+
+        store.record("/a", StatusCode::OK, Duration::from_millis(1));
+        store.record("/b", StatusCode::OK, Duration::from_millis(1));
+        store.record("/c", StatusCode::OK, Duration::from_millis(1));
+        store.record("/d", StatusCode::OK, Duration::from_millis(1));
+        store.record("/a", StatusCode::OK, Duration::from_millis(1));
+
+        let snapshot = store.snapshot();
+
+        assert_eq!(
+            snapshot.keys().collect::<Vec<_>>(),
+            vec!["/a", "/b", OTHER_PATHS]
+        );
+        assert_eq!(snapshot["/a"].count, 2);
+        assert_eq!(snapshot[OTHER_PATHS].count, 2);
+    }
+}
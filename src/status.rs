@@ -0,0 +1,38 @@
+use actix_web::http::StatusCode;
+
+use crate::help::SpecialParam;
+
+pub const STATUS_PARAM: &str = "__status";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: STATUS_PARAM,
+    value: "<100-599>",
+    description: "Answers with the given status, still echoing the request",
+}];
+
+/// Status forced by `__status`, if it's a valid code.
+pub fn parse(value: &str) -> Option<StatusCode> {
+    value
+        .parse::<u16>()
+        .ok()
+        .filter(|code| (100..=599).contains(code))
+        .and_then(|code| StatusCode::from_u16(code).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("418", Some(StatusCode::IM_A_TEAPOT))]
+    #[case("100", Some(StatusCode::CONTINUE))]
+    #[case("599", Some(StatusCode::from_u16(599).unwrap()))]
+    #[case("99", None)]
+    #[case("600", None)]
+    #[case("abc", None)]
+    #[case("", None)]
+    fn test_parse(#[case] value: &str, #[case] expected: Option<StatusCode>) {
+        assert_eq!(parse(value), expected);
+    }
+}
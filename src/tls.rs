@@ -0,0 +1,85 @@
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+
+/// TLS configuration of the server, when both a certificate and its key are set.
+pub fn server_config(
+    cert_path: Option<&str>,
+    key_path: Option<&str>,
+) -> Result<Option<ServerConfig>, String> {
+    let (cert_path, key_path) = match (cert_path, key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) => return Ok(None),
+        (Some(_), None) => return Err("tls_cert_path is set without tls_key_path".to_string()),
+        (None, Some(_)) => return Err("tls_key_path is set without tls_cert_path".to_string()),
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("invalid TLS certificate {}: {}", cert_path, e))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("invalid TLS key {}: {}", key_path, e))?;
+
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map(Some)
+        .map_err(|e| format!("invalid TLS certificate or key: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn write_self_signed(name: &str) -> (PathBuf, PathBuf) {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let dir = std::env::temp_dir();
+        let cert_path = dir.join(format!("catchall-{}-{}.crt", name, std::process::id()));
+        let key_path = dir.join(format!("catchall-{}-{}.key", name, std::process::id()));
+        std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+        std::fs::write(&key_path, certified.signing_key.serialize_pem()).unwrap();
+
+        (cert_path, key_path)
+    }
+
+    #[test]
+    fn test_server_config_loads_self_signed_cert() {
+        let (cert_path, key_path) = write_self_signed("valid");
+
+        let config = server_config(cert_path.to_str(), key_path.to_str());
+
+        assert!(matches!(config, Ok(Some(_))));
+    }
+
+    #[test]
+    fn test_server_config_is_disabled_without_paths() {
+        assert!(matches!(server_config(None, None), Ok(None)));
+    }
+
+    #[test]
+    fn test_server_config_requires_both_paths() {
+        assert_eq!(
+            server_config(Some("cert.pem"), None).err(),
+            Some("tls_cert_path is set without tls_key_path".to_string())
+        );
+        assert_eq!(
+            server_config(None, Some("key.pem")).err(),
+            Some("tls_key_path is set without tls_cert_path".to_string())
+        );
+    }
+
+    #[test]
+    fn test_server_config_rejects_mismatched_key() {
+        let (cert_path, _) = write_self_signed("cert");
+        let (_, key_path) = write_self_signed("key");
+
+        assert!(server_config(cert_path.to_str(), key_path.to_str()).is_err());
+    }
+
+    #[test]
+    fn test_server_config_rejects_missing_files() {
+        assert!(server_config(Some("/nonexistent.crt"), Some("/nonexistent.key")).is_err());
+    }
+}
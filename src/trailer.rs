@@ -0,0 +1,55 @@
+use actix_web::http::header::{HeaderName, HeaderValue};
+
+use crate::help::SpecialParam;
+
+pub const TRAILER_PARAM: &str = "__trailer";
+
+pub const HELP: &[SpecialParam] = &[SpecialParam {
+    name: TRAILER_PARAM,
+    value: "<Name>:<value>",
+    description: "Answers `501`, as actix-web can't send HTTP trailers",
+}];
+
+/// Parses a `__trailer` value of the form `Name:value`.
+pub fn parse(spec: &str) -> Option<(HeaderName, HeaderValue)> {
+    let (name, value) = spec.split_once(':')?;
+
+    Some((
+        HeaderName::try_from(name.trim()).ok()?,
+        HeaderValue::try_from(value.trim()).ok()?,
+    ))
+}
+
+/// Why a trailer can't be sent: actix-http writes the final chunk of a chunked body without any
+/// trailer field, and its `MessageBody` trait has no way to provide one.
+pub fn unsupported_reason(name: &HeaderName) -> String {
+    format!(
+        "cannot send the {} trailer: actix-web doesn't support HTTP trailers",
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("X-Checksum: abc"),
+            Some((
+                HeaderName::from_static("x-checksum"),
+                HeaderValue::from_static("abc")
+            ))
+        );
+    }
+
+    #[rstest]
+    #[case("X-Checksum")]
+    #[case("X Checksum:abc")]
+    #[case(":abc")]
+    fn test_parse_invalid(#[case] spec: &str) {
+        assert_eq!(parse(spec), None);
+    }
+}
@@ -0,0 +1,91 @@
+use jaq_core::{
+    data::JustLut,
+    load::{Arena, File, Loader},
+    unwrap_valr, Compiler, Ctx, Filter, Vars,
+};
+use jaq_json::{read, Val};
+use serde_json::Value;
+
+/// A jq filter producing the response body from the JSON body of the request.
+pub struct JqTransform {
+    filter: Filter<JustLut<Val>>,
+}
+
+impl std::fmt::Debug for JqTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JqTransform").finish_non_exhaustive()
+    }
+}
+
+impl JqTransform {
+    pub fn new(code: &str) -> Result<Self, String> {
+        let defs = jaq_core::defs()
+            .chain(jaq_std::defs())
+            .chain(jaq_json::defs());
+        let funs = jaq_core::funs()
+            .chain(jaq_std::funs())
+            .chain(jaq_json::funs());
+
+        let arena = Arena::default();
+        let modules = Loader::new(defs)
+            .load(&arena, File { code, path: () })
+            .map_err(|errs| format!("invalid jq transform {:?}: {:?}", code, errs))?;
+        let filter = Compiler::default()
+            .with_funs(funs)
+            .compile(modules)
+            .map_err(|errs| format!("invalid jq transform {:?}: {:?}", code, errs))?;
+
+        Ok(Self { filter })
+    }
+
+    /// Runs the filter on `input`. A filter yielding several values produces an array of them.
+    pub fn apply(&self, input: &Value) -> Result<Value, String> {
+        let input = read::parse_single(input.to_string().as_bytes()).map_err(|e| e.to_string())?;
+        let ctx = Ctx::<JustLut<Val>>::new(&self.filter.lut, Vars::new([]));
+
+        let mut outputs = self
+            .filter
+            .id
+            .run((ctx, input))
+            .map(unwrap_valr)
+            .map(|output| {
+                let output = output.map_err(|e| format!("jq transform failed: {}", e))?;
+                serde_json::from_str(&output.to_string())
+                    .map_err(|e| format!("jq transform produced invalid JSON: {}", e))
+            })
+            .collect::<Result<Vec<Value>, String>>()?;
+
+        Ok(match outputs.len() {
+            1 => outputs.remove(0),
+            _ => Value::Array(outputs),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(".foo | ascii_upcase", json!({"foo": "bar"}), json!("BAR"))]
+    #[case("{total: (.items | map(.price) | add)}", json!({"items": [{"price": 2}, {"price": 3}]}), json!({"total": 5}))]
+    #[case(".[]", json!([1, 2]), json!([1, 2]))]
+    fn test_apply(#[case] code: &str, #[case] input: Value, #[case] expected: Value) {
+        assert_eq!(JqTransform::new(code).unwrap().apply(&input), Ok(expected));
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_filter() {
+        assert!(JqTransform::new(".foo |").is_err());
+        assert!(JqTransform::new("nope").is_err());
+    }
+
+    #[test]
+    fn test_apply_reports_runtime_errors() {
+        let transform = JqTransform::new(".foo | ascii_upcase").unwrap();
+
+        assert!(transform.apply(&json!({"foo": 1})).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+
+/// Removes the socket file left behind at `path`, e.g. by a server that didn't shut down cleanly.
+/// Anything else than a socket is left in place, and reported as an error.
+pub fn remove_stale(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} exists and isn't a socket", path.display()),
+        )),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("catchall-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_remove_stale_removes_sockets() {
+        let path = temp_path("stale.sock");
+        let _ = fs::remove_file(&path);
+        drop(UnixListener::bind(&path).unwrap());
+
+        remove_stale(&path).unwrap();
+
+        assert!(!path.exists());
+        assert!(remove_stale(&path).is_ok());
+    }
+
+    #[test]
+    fn test_remove_stale_keeps_other_files() {
+        let path = temp_path("regular.file");
+        fs::write(&path, "data").unwrap();
+
+        assert_eq!(
+            remove_stale(&path).unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+        assert!(path.exists());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
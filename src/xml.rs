@@ -0,0 +1,96 @@
+use quick_xml::{
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Writer,
+};
+use serde_json::Value;
+
+pub const XML_CONTENT_TYPE: &str = "application/xml";
+
+const ROOT: &str = "request";
+const ITEM: &str = "item";
+/// Element of the fields whose name isn't a valid XML name, e.g. a `1` query parameter, with the
+/// name in its `key` attribute.
+const ENTRY: &str = "entry";
+
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !name.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("xml"))
+}
+
+fn write_element(writer: &mut Writer<Vec<u8>>, name: &str, value: &Value) -> std::io::Result<()> {
+    let (tag, start) = if is_xml_name(name) {
+        (name, BytesStart::new(name))
+    } else {
+        (
+            ENTRY,
+            BytesStart::new(ENTRY).with_attributes([("key", name)]),
+        )
+    };
+
+    let text = match value {
+        Value::Null => return writer.write_event(Event::Empty(start)),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        Value::Array(_) | Value::Object(_) => None,
+    };
+
+    writer.write_event(Event::Start(start))?;
+    match (text, value) {
+        (Some(text), _) => writer.write_event(Event::Text(BytesText::new(&text)))?,
+        (None, Value::Array(items)) => {
+            for item in items {
+                write_element(writer, ITEM, item)?;
+            }
+        }
+        (None, Value::Object(fields)) => {
+            for (key, field) in fields {
+                write_element(writer, key, field)?;
+            }
+        }
+        _ => {}
+    }
+    writer.write_event(Event::End(BytesEnd::new(tag)))
+}
+
+/// Serializes the echo as XML, under a `<request>` root element.
+///
+/// Fields become elements, array items `<item>` elements and `null` an empty element.
+pub fn to_xml(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(Vec::new());
+
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .and_then(|_| write_element(&mut writer, ROOT, value))
+        .expect("writing to a Vec");
+
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_xml() {
+        let value = json!({
+            "method": "GET",
+            "tags": ["a", 1],
+            "headers": {"x-tenant": "<acme>", "1st": true, "xmlns": null},
+        });
+
+        assert_eq!(
+            String::from_utf8(to_xml(&value)).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><request>\
+             <headers><entry key=\"1st\">true</entry><x-tenant>&lt;acme&gt;</x-tenant>\
+             <entry key=\"xmlns\"/></headers>\
+             <method>GET</method><tags><item>a</item><item>1</item></tags></request>"
+        );
+    }
+}